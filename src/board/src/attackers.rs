@@ -0,0 +1,143 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::Position;
+use types::attacks;
+use types::*;
+
+impl Position {
+
+    /// Returns the pieces of both colors which attack the given square with the
+    /// given occupied squares. Passing a modified occupancy allows looking through
+    /// pieces, e.g. for x-rays.
+    pub fn attackers_to(&self, sq: Square, occupied: Bitboard) -> Bitboard {
+        let queens = self.pieces_by_type(PieceType::Queen);
+        (attacks::pawn(Color::White, sq) & self.pieces(Piece::BlackPawn))
+            | (attacks::pawn(Color::Black, sq) & self.pieces(Piece::WhitePawn))
+            | (attacks::knight(sq) & self.pieces_by_type(PieceType::Knight))
+            | (attacks::bishop(sq, occupied) & (self.pieces_by_type(PieceType::Bishop) | queens))
+            | (attacks::rook(sq, occupied) & (self.pieces_by_type(PieceType::Rook) | queens))
+            | (attacks::king(sq) & self.pieces_by_type(PieceType::King))
+    }
+
+    /// Returns true if any piece of the given color attacks the square.
+    #[inline]
+    pub fn is_attacked(&self, sq: Square, by: Color) -> bool {
+        !(self.attackers_to(sq, self.occupied()) & self.occupied_by(by)).is_empty()
+    }
+
+    /// Returns the enemy pieces giving check to the king of the side to move.
+    #[inline]
+    pub fn checkers(&self) -> Bitboard {
+        let us = self.side_to_move();
+        self.attackers_to(self.king_square(us), self.occupied()) & self.occupied_by(us.flip())
+    }
+
+    /// Returns true if the king of the side to move is in check.
+    #[inline]
+    pub fn in_check(&self) -> bool { !self.checkers().is_empty() }
+
+    /// Returns the pieces of the given color which are pinned to their own king
+    /// by an enemy slider.
+    #[inline]
+    pub fn pinned(&self, c: Color) -> Bitboard { self.blockers_for_king(c) & self.occupied_by(c) }
+
+    /// Returns the pieces of the side to move which stand between one of its own
+    /// sliders and the enemy king and give discovered check when moving off the line.
+    #[inline]
+    pub fn discovered_check_candidates(&self) -> Bitboard {
+        let us = self.side_to_move();
+        self.blockers_for_king(us.flip()) & self.occupied_by(us)
+    }
+
+    /// Returns the pieces of both colors which are the only piece between the king
+    /// of the given color and an enemy slider. Own blockers are pinned, enemy
+    /// blockers can give discovered check.
+    pub(crate) fn blockers_for_king(&self, c: Color) -> Bitboard {
+        let ksq = self.king_square(c);
+        let them = c.flip();
+        let queens = self.pieces_of(them, PieceType::Queen);
+        let snipers = (attacks::rook(ksq, Bitboard::EMPTY) & (self.pieces_of(them, PieceType::Rook) | queens))
+            | (attacks::bishop(ksq, Bitboard::EMPTY) & (self.pieces_of(them, PieceType::Bishop) | queens));
+        let occupied = self.occupied();
+        let mut blockers = Bitboard::EMPTY;
+        for sniper in snipers {
+            let between = ksq.between(sniper) & occupied;
+            if between.popcount() == 1 {
+                blockers |= between;
+            }
+        }
+        blockers
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn squares(list: &[Square]) -> Bitboard {
+        list.iter().fold(Bitboard::EMPTY, |bb, sq| bb | sq.bitboard())
+    }
+
+    #[test]
+    fn test_attackers_to() {
+        let pos = Position::from_str("4k3/8/2n5/3p4/4R3/5N2/3P4/4K3 w - - 0 1").unwrap();
+        let occupied = pos.occupied();
+        assert_eq!(squares(&[Square::D5]), pos.attackers_to(Square::E4, occupied));
+        assert_eq!(squares(&[Square::E4, Square::D2]), pos.attackers_to(Square::E3, occupied));
+        assert_eq!(squares(&[Square::C6, Square::F3, Square::E4]), pos.attackers_to(Square::D4, occupied));
+        // removing the front rook from the occupancy reveals the rook behind it
+        let pos = Position::from_str("4k3/8/8/8/8/8/4R3/4R1K1 w - - 0 1").unwrap();
+        let white = pos.occupied_by(Color::White);
+        let occupied = pos.occupied() ^ Square::E2.bitboard();
+        assert_eq!(squares(&[Square::E2]), pos.attackers_to(Square::E7, pos.occupied()) & white);
+        assert_eq!(squares(&[Square::E1]), pos.attackers_to(Square::E7, occupied) & occupied & white);
+    }
+
+    #[test]
+    fn test_is_attacked_and_checkers() {
+        let pos = Position::from_str("4k3/8/8/1B6/8/8/8/4K2r w - - 0 1").unwrap();
+        assert!(pos.is_attacked(Square::E8, Color::White));
+        assert!(pos.is_attacked(Square::F1, Color::Black));
+        assert!(!pos.is_attacked(Square::E2, Color::Black));
+        assert_eq!(Bitboard::from(Square::H1), pos.checkers());
+        assert!(pos.in_check());
+        assert!(!Position::new().in_check());
+    }
+
+    #[test]
+    fn test_pinned_and_discovered() {
+        // the black pawn is pinned by the rook
+        let pos = Position::from_str("4k3/4p3/8/8/8/8/6N1/4R2K w - - 0 1").unwrap();
+        assert_eq!(Bitboard::EMPTY, pos.pinned(Color::White));
+        assert_eq!(Bitboard::from(Square::E7), pos.pinned(Color::Black));
+        // the white bishop blocks the rook from the black king
+        let pos = Position::from_str("4k3/8/8/8/4B3/8/6N1/4R2K w - - 0 1").unwrap();
+        assert_eq!(Bitboard::from(Square::E4), pos.discovered_check_candidates());
+        // the white knight is pinned by the black bishop
+        let pos = Position::from_str("4k3/8/8/3b4/8/8/6N1/4R2K w - - 0 1").unwrap();
+        assert_eq!(Bitboard::from(Square::G2), pos.pinned(Color::White));
+        assert!(pos.discovered_check_candidates().is_empty());
+    }
+
+}
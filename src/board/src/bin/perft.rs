@@ -0,0 +1,85 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Counts the leaf nodes of the legal move tree and prints them per root move.
+//!
+//! Usage: perft <depth> [fen] [--threads n] [--hash megabytes]
+//!
+//! The FEN may be given as one quoted argument or as separate words and
+//! defaults to the start position.
+
+use board::{Position, START_FEN};
+use std::env;
+use std::process;
+use std::str::FromStr;
+use std::time::Instant;
+
+const USAGE: &str = "usage: perft <depth> [fen] [--threads n] [--hash megabytes]";
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("{}", USAGE);
+    process::exit(1)
+}
+
+fn main() {
+    let mut depth = None;
+    let mut fen = Vec::new();
+    let mut threads = 1;
+    let mut hash_mb = 0;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--threads" | "--hash" => {
+                let value = args.next()
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or_else(|| fail(&format!("{} needs a number", arg)));
+                if arg == "--threads" { threads = value } else { hash_mb = value }
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if depth.is_none() => {
+                depth = Some(arg.parse::<usize>().unwrap_or_else(|_| fail(&format!("invalid depth {}", arg))));
+            }
+            _ => fen.push(arg),
+        }
+    }
+
+    let depth = depth.unwrap_or_else(|| fail("missing depth"));
+    let fen = if fen.is_empty() { START_FEN.to_string() } else { fen.join(" ") };
+    let pos = Position::from_str(&fen).unwrap_or_else(|e| fail(&e.to_string()));
+
+    let start = Instant::now();
+    let divide = pos.divide_parallel(depth, threads, hash_mb);
+    let elapsed = start.elapsed();
+
+    for (m, nodes) in &divide {
+        println!("{}: {}", m, nodes);
+    }
+    let nodes: u64 = if depth == 0 { 1 } else { divide.iter().map(|(_, n)| n).sum() };
+    let nps = nodes as f64 / elapsed.as_secs_f64().max(1e-9);
+    println!();
+    println!("Nodes searched: {}", nodes);
+    println!("Time: {} ms ({:.0} nps)", elapsed.as_millis(), nps);
+}
@@ -0,0 +1,153 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::Position;
+use types::*;
+
+impl Position {
+
+    /// Returns how often the current position occurred before with the same side to
+    /// move. Only positions since the last capture, pawn move or null move count.
+    pub fn repetition_count(&self) -> usize {
+        let key = self.key();
+        self.previous_keys().skip(1).step_by(2).filter(|k| *k == key).count()
+    }
+
+    /// Returns true if the current position occurred the given number of times in
+    /// total, e.g. 2 for the twofold repetition used in search and 3 for the threefold
+    /// repetition rule.
+    #[inline]
+    pub fn is_repetition(&self, times: usize) -> bool { self.repetition_count() + 1 >= times }
+
+    /// Returns true if 50 moves were made by each side without a capture or pawn move
+    /// and the side to move is not checkmated, as checkmate takes priority.
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock() >= 100 && !(self.in_check() && self.legal_moves().is_empty())
+    }
+
+    /// Returns true if neither side has the material to checkmate: king against king
+    /// with at most one knight or bishop, or only bishops on squares of one color.
+    pub fn has_insufficient_material(&self) -> bool {
+        let heavy = self.pieces_by_type(PieceType::Pawn)
+            | self.pieces_by_type(PieceType::Rook)
+            | self.pieces_by_type(PieceType::Queen);
+        if !heavy.is_empty() {
+            return false;
+        }
+        let knights = self.pieces_by_type(PieceType::Knight);
+        let bishops = self.pieces_by_type(PieceType::Bishop);
+        if (knights | bishops).popcount() <= 1 {
+            return true;
+        }
+        let light = bishops.filter(|sq| sq.is_light()).count();
+        knights.is_empty() && (light == 0 || light == bishops.popcount() as usize)
+    }
+
+    /// Returns true if the position is a draw by threefold repetition, the fifty
+    /// move rule or insufficient material. Stalemate is left to the search.
+    pub fn is_draw(&self) -> bool {
+        self.is_repetition(3) || self.is_fifty_move_draw() || self.has_insufficient_material()
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn play(pos: &mut Position, moves: &str) {
+        for uci in moves.split_whitespace() {
+            let m = *pos.legal_moves().iter().find(|m| m.to_string() == uci).expect(uci);
+            pos.do_move(m);
+        }
+    }
+
+    #[test]
+    fn test_repetition() {
+        let mut pos = Position::new();
+        assert_eq!(0, pos.repetition_count());
+        play(&mut pos, "g1f3 g8f6 f3g1");
+        assert!(!pos.is_repetition(2));
+        play(&mut pos, "f6g8");
+        assert_eq!(1, pos.repetition_count());
+        assert!(pos.is_repetition(2));
+        assert!(!pos.is_repetition(3));
+        assert!(!pos.is_draw());
+        play(&mut pos, "g1f3 g8f6 f3g1 f6g8");
+        assert_eq!(2, pos.repetition_count());
+        assert!(pos.is_repetition(3));
+        assert!(pos.is_draw());
+    }
+
+    #[test]
+    fn test_repetition_window() {
+        // the pawn move resets the clock, earlier positions can't repeat
+        let mut pos = Position::new();
+        play(&mut pos, "g1f3 g8f6 f3g1 f6g8 e2e4 e7e5 g1f3 g8f6 f3g1 f6g8");
+        assert_eq!(1, pos.repetition_count());
+        // a null move ends the window as well
+        let mut pos = Position::new();
+        play(&mut pos, "g1f3 g8f6 f3g1 f6g8");
+        pos.do_null_move();
+        pos.do_null_move();
+        assert_eq!(0, pos.repetition_count());
+    }
+
+    #[test]
+    fn test_fifty_moves() {
+        let pos = Position::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        assert!(!pos.is_fifty_move_draw());
+        let pos = Position::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+        assert!(pos.is_fifty_move_draw());
+        assert!(pos.is_draw());
+        // a checkmate on the 100th half move is still a checkmate
+        let pos = Position::from_str("R3k3/8/4K3/8/8/8/8/8 b - - 100 80").unwrap();
+        assert!(pos.legal_moves().is_empty());
+        assert!(!pos.is_fifty_move_draw());
+        // a check which can be escaped is a draw
+        let pos = Position::from_str("R3k3/8/8/8/8/8/8/4K3 b - - 100 80").unwrap();
+        assert!(pos.is_fifty_move_draw());
+    }
+
+    #[test]
+    fn test_insufficient_material() {
+        for fen in ["4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+                    "4k3/8/8/8/8/8/8/4KN2 w - - 0 1",
+                    "4k3/8/8/8/8/8/8/4KB2 w - - 0 1",
+                    "4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1",
+                    "4k3/8/8/8/8/8/7b/B1B1K3 w - - 0 1"] {
+            let pos = Position::from_str(fen).unwrap();
+            assert!(pos.has_insufficient_material(), "{}", fen);
+            assert!(pos.is_draw(), "{}", fen);
+        }
+        for fen in ["4k3/8/8/8/8/8/7P/4K3 w - - 0 1",
+                    "4k3/8/8/8/8/8/8/3NKN2 w - - 0 1",
+                    "4kn2/8/8/8/8/8/8/4KB2 w - - 0 1",
+                    "5b2/4k3/8/8/8/8/8/4KB2 w - - 0 1",
+                    "4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1",
+                    "4k3/8/8/8/8/8/8/4K2R w - - 0 1"] {
+            let pos = Position::from_str(fen).unwrap();
+            assert!(!pos.has_insufficient_material(), "{}", fen);
+        }
+    }
+
+}
@@ -0,0 +1,12 @@
+mod position;
+mod attackers;
+mod move_list;
+mod movegen;
+mod perft;
+mod see;
+mod draw;
+
+pub use crate::position::*;
+pub use crate::move_list::*;
+pub use crate::movegen::*;
+pub use crate::perft::*;
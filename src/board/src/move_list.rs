@@ -0,0 +1,131 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::fmt;
+use std::ops::Deref;
+use types::Move;
+
+/// Maximum number of moves in any legal chess position is 218, this leaves some room
+/// for the pseudo legal moves
+pub const MAX_MOVES: usize = 256;
+
+/// A fixed capacity list of moves which lives on the stack so that move
+/// generation does not allocate. Dereferences to a slice of the moves.
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+
+    /// Returns an empty move list.
+    #[inline]
+    pub fn new() -> MoveList {
+        MoveList { moves: [Move::NULL; MAX_MOVES], len: 0 }
+    }
+
+    /// Appends the move to the list.
+    ///
+    /// # Panics
+    /// Panics if the list is full.
+    #[inline]
+    pub fn push(&mut self, m: Move) {
+        self.moves[self.len] = m;
+        self.len += 1;
+    }
+
+    /// Removes all moves from the list.
+    #[inline]
+    pub fn clear(&mut self) { self.len = 0; }
+
+    /// Keeps only the moves for which the predicate returns true and preserves their order.
+    #[inline]
+    pub fn retain<F: FnMut(Move) -> bool>(&mut self, mut f: F) {
+        let mut kept = 0;
+        for i in 0..self.len {
+            let m = self.moves[i];
+            if f(m) {
+                self.moves[kept] = m;
+                kept += 1;
+            }
+        }
+        self.len = kept;
+    }
+
+}
+
+impl Default for MoveList {
+    fn default() -> Self { MoveList::new() }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+    #[inline]
+    fn deref(&self) -> &[Move] { &self.moves[..self.len] }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Prints the moves in UCI notation separated by spaces.
+impl fmt::Display for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, m) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", m)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::Square;
+
+    #[test]
+    fn test_push_retain() {
+        let mut list = MoveList::new();
+        assert!(list.is_empty());
+        list.push(Move::new(Square::E2, Square::E4));
+        list.push(Move::new(Square::D2, Square::D4));
+        list.push(Move::new(Square::G1, Square::F3));
+        assert_eq!(3, list.len());
+        assert_eq!("e2e4 d2d4 g1f3", list.to_string());
+        list.retain(|m| m.from() != Square::D2);
+        assert_eq!(&[Move::new(Square::E2, Square::E4), Move::new(Square::G1, Square::F3)], &list[..]);
+        list.clear();
+        assert_eq!(0, list.len());
+    }
+
+}
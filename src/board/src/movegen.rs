@@ -0,0 +1,478 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::position::castling_rook_squares;
+use crate::{MoveList, Position};
+use types::attacks;
+use types::*;
+
+/// The kinds of moves a generation stage produces. Captures and Quiets together
+/// are all pseudo legal moves of a position which is not in check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GenType {
+    /// Captures including en passant and all promotions to a queen.
+    Captures,
+    /// Non captures including castling and all under promotions.
+    Quiets,
+    /// Moves which might get the king out of check. Only valid when in check.
+    Evasions,
+    /// Non captures which give check, excluding promotions. Only valid when not in check.
+    QuietChecks,
+    /// Captures and Quiets. Only valid when not in check.
+    NonEvasions,
+}
+
+impl Position {
+
+    /// Appends the pseudo legal moves of the given stage to the list. Pseudo legal
+    /// moves may leave the own king in check, except for castling which is only
+    /// generated if the king does not start on, pass or land on an attacked square.
+    pub fn generate(&self, gen: GenType, list: &mut MoveList) {
+        let us = self.side_to_move();
+        match gen {
+            GenType::Captures => self.generate_all(gen, self.occupied_by(us.flip()), list),
+            GenType::Quiets => self.generate_all(gen, !self.occupied(), list),
+            GenType::NonEvasions => self.generate_all(gen, !self.occupied_by(us), list),
+            GenType::Evasions => self.generate_evasions(list),
+            GenType::QuietChecks => {
+                let mut quiets = MoveList::new();
+                self.generate_all(GenType::Quiets, !self.occupied(), &mut quiets);
+                for m in &quiets {
+                    if m.move_type() != MoveType::Promotion && self.gives_check(*m) {
+                        list.push(*m);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns all pseudo legal moves of the position, only evasions when in check.
+    pub fn pseudo_legal_moves(&self) -> MoveList {
+        let mut list = MoveList::new();
+        let gen = if self.in_check() { GenType::Evasions } else { GenType::NonEvasions };
+        self.generate(gen, &mut list);
+        list
+    }
+
+    /// Returns all legal moves of the position. Checkers and pinned pieces are
+    /// computed once, only evasions are generated when in check and each move is
+    /// then checked cheaply without making it.
+    pub fn legal_moves(&self) -> MoveList {
+        let mut list = MoveList::new();
+        let checkers = self.checkers();
+        let gen = if checkers.is_empty() { GenType::NonEvasions } else { GenType::Evasions };
+        self.generate(gen, &mut list);
+        let pinned = self.pinned(self.side_to_move());
+        list.retain(|m| self.is_legal_with(m, pinned));
+        list
+    }
+
+    /// Returns true if the given pseudo legal move does not leave the own king in check.
+    pub fn is_legal(&self, m: Move) -> bool {
+        self.is_legal_with(m, self.pinned(self.side_to_move()))
+    }
+
+    /// Checks the legality of a pseudo legal move given the pinned pieces of the side to move.
+    /// A pinned piece may only move along the line through its king. The king may not
+    /// step onto an attacked square, which includes squares behind it on a checking
+    /// slider's line. En passant removes two pieces from a line and is checked by
+    /// looking for sliders attacking the king after the capture.
+    fn is_legal_with(&self, m: Move, pinned: Bitboard) -> bool {
+        let us = self.side_to_move();
+        let them = us.flip();
+        let from = m.from();
+        let to = m.to();
+        let ksq = self.king_square(us);
+
+        match m.move_type() {
+            // castling is only generated if the king does not cross an attacked square
+            MoveType::Castling => true,
+            MoveType::EnPassant => {
+                let captured = Square::from_file_rank(to.file_of(), from.rank_of());
+                let occupied = (self.occupied() ^ from.bitboard() ^ captured.bitboard()) | to.bitboard();
+                let queens = self.pieces_of(them, PieceType::Queen);
+                (attacks::rook(ksq, occupied) & (self.pieces_of(them, PieceType::Rook) | queens)).is_empty()
+                    && (attacks::bishop(ksq, occupied) & (self.pieces_of(them, PieceType::Bishop) | queens)).is_empty()
+            }
+            _ if from == ksq => {
+                let occupied = self.occupied() ^ from.bitboard();
+                (self.attackers_to(to, occupied) & self.occupied_by(them)).is_empty()
+            }
+            _ => !pinned.has(from) || Square::aligned(from, to, ksq),
+        }
+    }
+
+    /// Returns true if the given pseudo legal move gives check to the opponent,
+    /// either directly by the moved piece or discovered by a slider behind it.
+    pub fn gives_check(&self, m: Move) -> bool {
+        let us = self.side_to_move();
+        let ksq = self.king_square(us.flip());
+        let from = m.from();
+        let to = m.to();
+        let mut occupied = (self.occupied() ^ from.bitboard()) | to.bitboard();
+        let (moved, to) = match m.move_type() {
+            MoveType::Castling => {
+                let (rook_from, rook_to) = castling_rook_squares(from, to);
+                occupied ^= rook_from.bitboard() | rook_to.bitboard();
+                (Piece::new(us, PieceType::Rook), rook_to)
+            }
+            MoveType::EnPassant => {
+                occupied ^= Square::from_file_rank(to.file_of(), from.rank_of()).bitboard();
+                (Piece::new(us, PieceType::Pawn), to)
+            }
+            MoveType::Promotion => (Piece::new(us, m.promotion().unwrap()), to),
+            MoveType::Normal => (self.piece_on(from).unwrap(), to),
+        };
+        if attacks::piece(moved, to, occupied).has(ksq) {
+            return true;
+        }
+        let queens = self.pieces_of(us, PieceType::Queen);
+        let sliders = (attacks::bishop(ksq, occupied) & (self.pieces_of(us, PieceType::Bishop) | queens))
+            | (attacks::rook(ksq, occupied) & (self.pieces_of(us, PieceType::Rook) | queens));
+        !(sliders & !from.bitboard()).is_empty()
+    }
+
+    /// Generates king moves out of check and, in single check, moves which
+    /// capture the checker or block the check.
+    fn generate_evasions(&self, list: &mut MoveList) {
+        let us = self.side_to_move();
+        let ksq = self.king_square(us);
+        let checkers = self.checkers();
+        debug_assert!(!checkers.is_empty());
+
+        // the king can't step back along the line of a slider giving check
+        let sliders = checkers & !self.pieces_by_type(PieceType::Pawn) & !self.pieces_by_type(PieceType::Knight);
+        let mut slider_attacks = Bitboard::EMPTY;
+        for checker in sliders {
+            slider_attacks |= checker.line(ksq) ^ checker.bitboard();
+        }
+        for to in attacks::king(ksq) & !self.occupied_by(us) & !slider_attacks {
+            list.push(Move::new(ksq, to));
+        }
+        if checkers.popcount() > 1 {
+            return;
+        }
+
+        let checker = checkers.lsb();
+        self.generate_pawn_moves(GenType::Evasions, ksq.between(checker) | checker.bitboard(), list);
+        self.generate_piece_moves(ksq.between(checker) | checker.bitboard(), list);
+    }
+
+    /// Generates the moves of all pieces to the target squares. Stage specific
+    /// rules apply to pawns, the king and castling.
+    fn generate_all(&self, gen: GenType, target: Bitboard, list: &mut MoveList) {
+        let us = self.side_to_move();
+        self.generate_pawn_moves(gen, target, list);
+        self.generate_piece_moves(target, list);
+
+        let ksq = self.king_square(us);
+        for to in attacks::king(ksq) & target {
+            list.push(Move::new(ksq, to));
+        }
+        if gen == GenType::Quiets || gen == GenType::NonEvasions {
+            self.generate_castling(list);
+        }
+    }
+
+    /// Generates the moves of knights, bishops, rooks and queens to the target squares.
+    fn generate_piece_moves(&self, target: Bitboard, list: &mut MoveList) {
+        let us = self.side_to_move();
+        let occupied = self.occupied();
+        for pt in [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen] {
+            let p = Piece::new(us, pt);
+            for from in self.pieces(p) {
+                for to in attacks::piece(p, from, occupied) & target {
+                    list.push(Move::new(from, to));
+                }
+            }
+        }
+    }
+
+    /// Generates pawn pushes, captures and promotions. The squares are computed for
+    /// both colors alike by shifting into the color's forward direction.
+    fn generate_pawn_moves(&self, gen: GenType, target: Bitboard, list: &mut MoveList) {
+        let us = self.side_to_move();
+        let them = us.flip();
+        let up = us.forward();
+        let push = 8 * us.direction();
+        let from = |to: Square, delta: i32| Square::wrapping_from_index((to.to_index() as i32 - delta) as usize);
+
+        let pawns = self.pieces_of(us, PieceType::Pawn);
+        let on_seventh = pawns & Rank::Seven.relative_to(us).bitboard();
+        let not_on_seventh = pawns & !on_seventh;
+        let empty = !self.occupied();
+        let enemies = match gen {
+            GenType::Evasions => self.occupied_by(them) & target,
+            _ => self.occupied_by(them),
+        };
+        let blocks = match gen {
+            GenType::Evasions => target,
+            _ => Bitboard::ALL,
+        };
+
+        if gen != GenType::Captures {
+            let single = not_on_seventh.shift(up) & empty;
+            let double = (single & Rank::Three.relative_to(us).bitboard()).shift(up) & empty;
+            for to in single & blocks {
+                list.push(Move::new(from(to, push), to));
+            }
+            for to in double & blocks {
+                list.push(Move::new(from(to, 2 * push), to));
+            }
+        }
+
+        if !on_seventh.is_empty() {
+            let pushed = on_seventh.shift(up);
+            for to in pushed & empty & blocks {
+                self.push_promotions(gen, from(to, push), to, list);
+            }
+            for to in pushed.shift(Direction::East) & enemies {
+                self.push_promotions(gen, from(to, push + 1), to, list);
+            }
+            for to in pushed.shift(Direction::West) & enemies {
+                self.push_promotions(gen, from(to, push - 1), to, list);
+            }
+        }
+
+        if gen != GenType::Quiets {
+            let pushed = not_on_seventh.shift(up);
+            for to in pushed.shift(Direction::East) & enemies {
+                list.push(Move::new(from(to, push + 1), to));
+            }
+            for to in pushed.shift(Direction::West) & enemies {
+                list.push(Move::new(from(to, push - 1), to));
+            }
+            if let Some(ep) = self.en_passant_capture() {
+                // when in check en passant only helps if the pawn which just moved gives check
+                if gen == GenType::Evasions && !target.has(from(ep, push)) {
+                    return;
+                }
+                for pawn in not_on_seventh & attacks::pawn(them, ep) {
+                    list.push(Move::new_en_passant(pawn, ep));
+                }
+            }
+        }
+    }
+
+    /// Pushes the promotions of the given stage: queen promotions are captures,
+    /// under promotions are quiet moves.
+    fn push_promotions(&self, gen: GenType, from: Square, to: Square, list: &mut MoveList) {
+        if gen != GenType::Quiets {
+            list.push(Move::new_promotion(from, to, PieceType::Queen));
+        }
+        if gen != GenType::Captures {
+            for pt in [PieceType::Knight, PieceType::Rook, PieceType::Bishop] {
+                list.push(Move::new_promotion(from, to, pt));
+            }
+        }
+    }
+
+    /// Generates castling moves for which the rights are present, the squares between
+    /// king and rook are empty and the king does not cross an attacked square.
+    fn generate_castling(&self, list: &mut MoveList) {
+        let us = self.side_to_move();
+        let rank = us.back_rank();
+        let ksq = Square::from_file_rank(File::E, rank);
+        if self.piece_on(ksq) != Some(Piece::new(us, PieceType::King)) {
+            return;
+        }
+        let sides = [
+            (CastlingRights::king_side(us), File::H, File::G, File::F),
+            (CastlingRights::queen_side(us), File::A, File::C, File::D),
+        ];
+        for (right, rook_file, king_file, transit_file) in sides {
+            let rook_sq = Square::from_file_rank(rook_file, rank);
+            let to = Square::from_file_rank(king_file, rank);
+            let transit = Square::from_file_rank(transit_file, rank);
+            if !self.castling_rights().has(right)
+                || self.piece_on(rook_sq) != Some(Piece::new(us, PieceType::Rook))
+                || !(ksq.between(rook_sq) & self.occupied()).is_empty()
+                || [ksq, transit, to].iter().any(|sq| self.is_attacked(*sq, us.flip())) {
+                continue;
+            }
+            list.push(Move::new_castling(ksq, to));
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::START_FEN;
+    use std::str::FromStr;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    fn generate(fen: &str, gen: GenType) -> Vec<String> {
+        let pos = Position::from_str(fen).unwrap();
+        let mut list = MoveList::new();
+        pos.generate(gen, &mut list);
+        let mut moves: Vec<String> = list.iter().map(|m| m.to_string()).collect();
+        moves.sort();
+        moves
+    }
+
+    #[test]
+    fn test_start_position() {
+        assert_eq!(20, generate(START_FEN, GenType::NonEvasions).len());
+        assert_eq!(20, generate(START_FEN, GenType::Quiets).len());
+        assert!(generate(START_FEN, GenType::Captures).is_empty());
+        assert!(generate(START_FEN, GenType::QuietChecks).is_empty());
+    }
+
+    #[test]
+    fn test_stages_partition() {
+        for fen in [START_FEN, KIWIPETE, "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+                    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"] {
+            let mut stages = generate(fen, GenType::Captures);
+            stages.extend(generate(fen, GenType::Quiets));
+            stages.sort();
+            assert_eq!(generate(fen, GenType::NonEvasions), stages, "{}", fen);
+        }
+        // kiwipete has 48 legal moves and no pseudo legal move which is illegal
+        assert_eq!(48, generate(KIWIPETE, GenType::NonEvasions).len());
+        assert_eq!(8, generate(KIWIPETE, GenType::Captures).len());
+    }
+
+    #[test]
+    fn test_black_pawns() {
+        let fen = "4k3/3p4/8/8/1p6/8/p6p/4K1N1 b - - 0 1";
+        assert_eq!(vec!["a2a1q", "h2g1q", "h2h1q"], generate(fen, GenType::Captures));
+        assert_eq!(vec!["a2a1b", "a2a1n", "a2a1r", "b4b3", "d7d5", "d7d6", "e8d8", "e8e7", "e8f7", "e8f8",
+                        "h2g1b", "h2g1n", "h2g1r", "h2h1b", "h2h1n", "h2h1r"],
+                   generate(fen, GenType::Quiets));
+    }
+
+    #[test]
+    fn test_en_passant() {
+        let fen = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+        assert_eq!(vec!["e5f6"], generate(fen, GenType::Captures));
+        let pos = Position::from_str(fen).unwrap();
+        let mut list = MoveList::new();
+        pos.generate(GenType::Captures, &mut list);
+        assert_eq!(MoveType::EnPassant, list[0].move_type());
+    }
+
+    #[test]
+    fn test_castling() {
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        let moves = generate(fen, GenType::Quiets);
+        assert!(moves.contains(&"e1g1".to_string()));
+        assert!(moves.contains(&"e1c1".to_string()));
+        // f1 attacked by the bishop and b1 blocked
+        let moves = generate("r3k2r/8/8/8/8/8/7b/RN2K2R w KQkq - 0 1", GenType::Quiets);
+        assert!(!moves.contains(&"e1g1".to_string()));
+        assert!(!moves.contains(&"e1c1".to_string()));
+        // b1 attacked does not matter for queen side castling
+        let moves = generate("1r2k2r/8/8/8/8/8/8/R3K2R w KQk - 0 1", GenType::Quiets);
+        assert!(moves.contains(&"e1c1".to_string()));
+    }
+
+    #[test]
+    fn test_evasions() {
+        // single check by a rook: king moves, block and capture
+        let fen = "4r1k1/8/8/8/8/8/3B4/R3K2N w - - 0 1";
+        assert_eq!(vec!["d2e3", "e1d1", "e1f1", "e1f2"], generate(fen, GenType::Evasions));
+        // double check: only king moves
+        let fen = "4r1k1/8/8/8/8/5n2/3B4/R3K2N w - - 0 1";
+        assert_eq!(vec!["e1d1", "e1f1", "e1f2"], generate(fen, GenType::Evasions));
+        // check by a double pushed pawn which can be captured en passant
+        let fen = "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1";
+        assert!(generate(fen, GenType::Evasions).contains(&"e4d3".to_string()));
+    }
+
+    /// Returns the pseudo legal moves which don't leave the king in check after making them.
+    fn legal_by_making(pos: &Position) -> Vec<Move> {
+        let mut pos = pos.clone();
+        let us = pos.side_to_move();
+        let mut legal = Vec::new();
+        for m in pos.pseudo_legal_moves().iter() {
+            pos.do_move(*m);
+            let ksq = pos.king_square(us);
+            if !pos.is_attacked(ksq, us.flip()) {
+                legal.push(*m);
+            }
+            pos.undo_move();
+        }
+        legal.sort_by_key(|m| m.0);
+        legal
+    }
+
+    fn check_legal(pos: &mut Position, depth: usize) {
+        let mut legal = pos.legal_moves().to_vec();
+        legal.sort_by_key(|m| m.0);
+        assert_eq!(legal_by_making(pos), legal, "{}", pos);
+        if depth > 1 {
+            for m in legal {
+                pos.do_move(m);
+                check_legal(pos, depth - 1);
+                pos.undo_move();
+            }
+        }
+    }
+
+    #[test]
+    fn test_legal_moves_match_making() {
+        for fen in [START_FEN, KIWIPETE,
+                    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+                    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+                    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+                    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10"] {
+            check_legal(&mut Position::from_str(fen).unwrap(), 3);
+        }
+    }
+
+    #[test]
+    fn test_legal_pins_and_en_passant() {
+        // a pinned pawn may only move along the pin line, a pinned knight not at all
+        let pos = Position::from_str("4r1k1/8/8/8/8/4P3/8/4K3 w - - 0 1").unwrap();
+        assert!(pos.legal_moves().contains(&Move::new(Square::E3, Square::E4)));
+        let pos = Position::from_str("6k1/8/8/8/7b/8/5P2/4K3 w - - 0 1").unwrap();
+        assert!(!pos.legal_moves().iter().any(|m| m.from() == Square::F2));
+        let pos = Position::from_str("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+        assert!(!pos.legal_moves().iter().any(|m| m.from() == Square::E2));
+        // capturing en passant would expose the king on the rank
+        let pos = Position::from_str("8/8/8/KPp4r/8/8/8/7k w - c6 0 2").unwrap();
+        assert!(!pos.legal_moves().iter().any(|m| m.move_type() == MoveType::EnPassant));
+        assert!(pos.pseudo_legal_moves().iter().any(|m| m.move_type() == MoveType::EnPassant));
+        // in double check only the king moves
+        let pos = Position::from_str("4r1k1/8/8/8/8/5n2/3B4/R3K2N w - - 0 1").unwrap();
+        assert!(pos.legal_moves().iter().all(|m| m.from() == Square::E1));
+        assert_eq!(3, pos.legal_moves().len());
+    }
+
+    #[test]
+    fn test_quiet_checks() {
+        // direct knight and rook checks, a discovered check by the bishop and castling into check
+        let fen = "5k2/8/8/8/8/8/2B5/4K2R w K - 0 1";
+        let checks = generate(fen, GenType::QuietChecks);
+        assert!(checks.contains(&"h1h8".to_string()));
+        assert!(checks.contains(&"h1f1".to_string()));
+        assert!(checks.contains(&"e1g1".to_string()));
+        assert!(!checks.contains(&"c2d3".to_string()));
+        let fen = "7k/8/8/8/8/2N5/8/B3K3 w - - 0 1";
+        let checks = generate(fen, GenType::QuietChecks);
+        assert_eq!(8, checks.iter().filter(|m| m.starts_with("c3")).count());
+        assert!(checks.iter().all(|m| m.starts_with("c3")));
+    }
+
+}
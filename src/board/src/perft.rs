@@ -0,0 +1,241 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::Position;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use types::zobrist::Key;
+use types::Move;
+
+/// An entry of the perft table. The depth is stored with the key as the same
+/// position can be reached at different remaining depths.
+#[derive(Clone, Copy, Default)]
+struct PerftEntry {
+    key: Key,
+    depth: usize,
+    nodes: u64,
+}
+
+/// A small always-replace transposition table for perft which stores the
+/// node counts of already counted subtrees.
+pub struct PerftTable {
+    entries: Vec<PerftEntry>,
+    mask: usize,
+}
+
+impl PerftTable {
+
+    /// Creates a table using at most the given number of megabytes, at least one entry.
+    pub fn new(megabytes: usize) -> PerftTable {
+        let capacity = (megabytes << 20) / std::mem::size_of::<PerftEntry>();
+        let len = if capacity < 2 { 1 } else { 1 << (usize::BITS - 1 - capacity.leading_zeros()) };
+        PerftTable { entries: vec![PerftEntry::default(); len], mask: len - 1 }
+    }
+
+    #[inline]
+    fn probe(&self, key: Key, depth: usize) -> Option<u64> {
+        let entry = &self.entries[key as usize & self.mask];
+        if entry.key == key && entry.depth == depth {
+            Some(entry.nodes)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn store(&mut self, key: Key, depth: usize, nodes: u64) {
+        self.entries[key as usize & self.mask] = PerftEntry { key, depth, nodes };
+    }
+
+}
+
+impl Position {
+
+    /// Counts the leaf nodes of the legal move tree of the given depth.
+    /// The moves of the last ply are counted without making them.
+    pub fn perft(&mut self, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        let mut nodes = 0;
+        for m in &moves {
+            self.do_move(*m);
+            nodes += self.perft(depth - 1);
+            self.undo_move();
+        }
+        nodes
+    }
+
+    /// Like perft() but looks up and stores the counts of subtrees in the table
+    /// so that transpositions are only counted once.
+    pub fn perft_hashed(&mut self, depth: usize, table: &mut PerftTable) -> u64 {
+        if depth <= 1 {
+            return self.perft(depth);
+        }
+        if let Some(nodes) = table.probe(self.key(), depth) {
+            return nodes;
+        }
+        let mut nodes = 0;
+        for m in &self.legal_moves() {
+            self.do_move(*m);
+            nodes += self.perft_hashed(depth - 1, table);
+            self.undo_move();
+        }
+        table.store(self.key(), depth, nodes);
+        nodes
+    }
+
+    /// Returns the perft node count of the given depth for each legal root move.
+    pub fn divide(&mut self, depth: usize) -> Vec<(Move, u64)> {
+        let mut result = Vec::new();
+        for m in &self.legal_moves() {
+            self.do_move(*m);
+            result.push((*m, self.perft(depth.saturating_sub(1))));
+            self.undo_move();
+        }
+        result
+    }
+
+    /// Like divide() but the root moves are shared out to the given number of
+    /// threads which each count on their own copy of the position. If hash_mb
+    /// is not zero each thread uses a perft table of that size.
+    pub fn divide_parallel(&self, depth: usize, threads: usize, hash_mb: usize) -> Vec<(Move, u64)> {
+        let moves = self.legal_moves();
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::with_capacity(moves.len()));
+        thread::scope(|s| {
+            for _ in 0..threads.max(1) {
+                s.spawn(|| {
+                    let mut pos = self.clone();
+                    let mut table = if hash_mb > 0 { Some(PerftTable::new(hash_mb)) } else { None };
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= moves.len() {
+                            break;
+                        }
+                        pos.do_move(moves[i]);
+                        let nodes = match table.as_mut() {
+                            Some(table) => pos.perft_hashed(depth.saturating_sub(1), table),
+                            None => pos.perft(depth.saturating_sub(1)),
+                        };
+                        pos.undo_move();
+                        results.lock().unwrap().push((i, moves[i], nodes));
+                    }
+                });
+            }
+        });
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(i, _, _)| *i);
+        results.into_iter().map(|(_, m, nodes)| (m, nodes)).collect()
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::START_FEN;
+    use std::str::FromStr;
+    use types::attacks::{self, Backend};
+
+    /// The reference positions with their node counts for depth 1 to 5.
+    const POSITIONS: [(&str, [u64; 5]); 6] = [
+        (START_FEN, [20, 400, 8_902, 197_281, 4_865_609]),
+        ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+         [48, 2_039, 97_862, 4_085_603, 193_690_690]),
+        ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", [14, 191, 2_812, 43_238, 674_624]),
+        ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+         [6, 264, 9_467, 422_333, 15_833_292]),
+        ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", [44, 1_486, 62_379, 2_103_487, 89_941_194]),
+        ("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+         [46, 2_079, 89_890, 3_894_594, 164_075_551]),
+    ];
+
+    #[test]
+    fn test_perft() {
+        for (fen, counts) in POSITIONS {
+            let mut pos = Position::from_str(fen).unwrap();
+            for depth in 1..=3 {
+                assert_eq!(counts[depth - 1], pos.perft(depth), "{} depth {}", fen, depth);
+            }
+            assert_eq!(Position::from_str(fen).unwrap(), pos);
+        }
+    }
+
+    #[test]
+    fn test_divide() {
+        let mut pos = Position::new();
+        let divide = pos.divide(3);
+        assert_eq!(20, divide.len());
+        assert_eq!(8_902, divide.iter().map(|(_, n)| n).sum::<u64>());
+        let e2e4 = divide.iter().find(|(m, _)| m.to_string() == "e2e4").unwrap();
+        assert_eq!(600, e2e4.1);
+    }
+
+    #[test]
+    fn test_perft_hashed_and_parallel() {
+        for (fen, counts) in POSITIONS {
+            let mut pos = Position::from_str(fen).unwrap();
+            let mut table = PerftTable::new(1);
+            assert_eq!(counts[3], pos.perft_hashed(4, &mut table), "{}", fen);
+            let divide = pos.divide_parallel(3, 4, 0);
+            assert_eq!(pos.divide(3), divide);
+            assert_eq!(counts[2], divide.iter().map(|(_, n)| n).sum::<u64>(), "{}", fen);
+        }
+    }
+
+    /// Counts with both slider attack backends, needs the pext feature and a CPU with BMI2.
+    #[test]
+    #[cfg_attr(not(feature = "pext"), ignore = "needs the pext feature")]
+    fn test_perft_backends() {
+        if !attacks::has_pext() {
+            eprintln!("test_perft_backends skipped: the CPU does not support BMI2");
+            return;
+        }
+        for (fen, counts) in POSITIONS {
+            let mut pos = Position::from_str(fen).unwrap();
+            assert_eq!(Backend::Pext, attacks::set_backend(Backend::Pext));
+            let pext = pos.perft(3);
+            assert_eq!(Backend::Magic, attacks::set_backend(Backend::Magic));
+            let magic = pos.perft(3);
+            attacks::set_backend(Backend::Pext);
+            assert_eq!(magic, pext, "{}", fen);
+            assert_eq!(counts[2], magic, "{}", fen);
+        }
+    }
+
+    /// Runs the deeper counts, best with `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_perft_deep() {
+        for (fen, counts) in POSITIONS {
+            let pos = Position::from_str(fen).unwrap();
+            let nodes: u64 = pos.divide_parallel(5, 8, 16).iter().map(|(_, n)| n).sum();
+            assert_eq!(counts[4], nodes, "{}", fen);
+        }
+    }
+
+}
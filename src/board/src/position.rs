@@ -0,0 +1,839 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::fmt;
+use std::str::FromStr;
+use types::*;
+use types::attacks;
+use types::zobrist::{self, Key};
+
+/// FEN of the standard start position
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Castling rights lost when a piece moves from or to the square, indexed by square
+static CASTLING_LOST: [CastlingRights; SQUARES_LEN] = init_castling_lost();
+
+const fn init_castling_lost() -> [CastlingRights; SQUARES_LEN] {
+    let mut lost = [CastlingRights::NONE; SQUARES_LEN];
+    let mut i = 0;
+    while i < SQUARES_LEN {
+        lost[i] = CastlingRights::lost_by(Square::wrapping_from_index(i));
+        i += 1;
+    }
+    lost
+}
+
+/// Returns the rook's from and to squares for a castling move given by the king's squares.
+#[inline]
+pub(crate) fn castling_rook_squares(from: Square, to: Square) -> (Square, Square) {
+    let rank = from.rank_of();
+    if to > from {
+        (Square::from_file_rank(File::H, rank), Square::from_file_rank(File::F, rank))
+    } else {
+        (Square::from_file_rank(File::A, rank), Square::from_file_rank(File::D, rank))
+    }
+}
+
+/// The state of a position which can't be restored from the move alone and is
+/// saved on the history stack before a move is made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Undo {
+    mv: Move,
+    captured: Option<Piece>,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    key: Key,
+}
+
+/// A chess position: piece placement (as mailbox and as bitboards per piece
+/// and per color), side to move, castling rights, en passant square and the
+/// move clocks. Moves are made and taken back with `do_move` and `undo_move`
+/// which update the Zobrist key incrementally.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Position {
+    board: [Option<Piece>; SQUARES_LEN],
+    pieces_bb: [Bitboard; PIECES_LEN],
+    colors_bb: [Bitboard; COLORS_LEN],
+    side_to_move: Color,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    key: Key,
+    history: Vec<Undo>,
+}
+
+impl Position {
+
+    /// Returns the standard start position.
+    pub fn new() -> Position {
+        Position::from_str(START_FEN).unwrap()
+    }
+
+    /// Returns a position without any pieces and White to move.
+    fn empty() -> Position {
+        Position {
+            board: [None; SQUARES_LEN],
+            pieces_bb: [Bitboard::EMPTY; PIECES_LEN],
+            colors_bb: [Bitboard::EMPTY; COLORS_LEN],
+            side_to_move: Color::White,
+            castling_rights: CastlingRights::NONE,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            key: 0,
+            history: Vec::new(),
+        }
+    }
+
+    /// Returns the piece on the given square or None if the square is empty.
+    #[inline]
+    pub fn piece_on(&self, sq: Square) -> Option<Piece> { self.board[sq.to_index()] }
+
+    /// Returns the squares of all pieces of the given kind.
+    #[inline]
+    pub fn pieces(&self, p: Piece) -> Bitboard { self.pieces_bb[p.index()] }
+
+    /// Returns the squares of all pieces of the given color and type.
+    #[inline]
+    pub fn pieces_of(&self, c: Color, pt: PieceType) -> Bitboard { self.pieces(Piece::new(c, pt)) }
+
+    /// Returns the squares of all pieces of the given type of both colors.
+    #[inline]
+    pub fn pieces_by_type(&self, pt: PieceType) -> Bitboard {
+        self.pieces_of(Color::White, pt) | self.pieces_of(Color::Black, pt)
+    }
+
+    /// Returns the squares of all pieces of the given color.
+    #[inline]
+    pub fn occupied_by(&self, c: Color) -> Bitboard { self.colors_bb[c.index()] }
+
+    /// Returns the squares of all pieces.
+    #[inline]
+    pub fn occupied(&self) -> Bitboard { self.colors_bb[0] | self.colors_bb[1] }
+
+    /// Returns the square of the king of the given color.
+    #[inline]
+    pub fn king_square(&self, c: Color) -> Square { self.pieces_of(c, PieceType::King).lsb() }
+
+    #[inline]
+    pub fn side_to_move(&self) -> Color { self.side_to_move }
+
+    #[inline]
+    pub fn castling_rights(&self) -> CastlingRights { self.castling_rights }
+
+    /// Returns the en passant square, if any. A square read from FEN is kept even
+    /// if no pawn can capture on it, see `en_passant_capture`.
+    #[inline]
+    pub fn en_passant(&self) -> Option<Square> { self.en_passant }
+
+    /// Returns the en passant square if a pawn of the side to move can capture on it.
+    /// Only this square is part of the key and used by move generation.
+    #[inline]
+    pub fn en_passant_capture(&self) -> Option<Square> {
+        self.en_passant.filter(|&ep| self.can_capture_en_passant(ep, self.side_to_move))
+    }
+
+    /// Returns the number of half moves since the last capture or pawn move.
+    #[inline]
+    pub fn halfmove_clock(&self) -> u32 { self.halfmove_clock }
+
+    /// Returns the number of the full move starting at 1 and incremented after Black's move.
+    #[inline]
+    pub fn fullmove_number(&self) -> u32 { self.fullmove_number }
+
+    /// Returns the Zobrist key of the position.
+    #[inline]
+    pub fn key(&self) -> Key { self.key }
+
+    /// Returns the number of moves made on this position which can be taken back.
+    #[inline]
+    pub fn ply(&self) -> usize { self.history.len() }
+
+    /// Returns the last move made or None if there is none.
+    #[inline]
+    pub fn last_move(&self) -> Option<Move> { self.history.last().map(|u| u.mv) }
+
+    /// Returns the keys of the earlier positions which may repeat the current one,
+    /// starting with the position before the last move. The positions before the
+    /// last capture, pawn move or null move are left out.
+    pub(crate) fn previous_keys(&self) -> impl Iterator<Item = Key> + '_ {
+        self.history.iter()
+            .rev()
+            .take(self.halfmove_clock as usize)
+            .take_while(|u| !u.mv.is_null())
+            .map(|u| u.key)
+    }
+
+    /// Puts the piece on the given empty square.
+    #[inline]
+    pub(crate) fn put_piece(&mut self, p: Piece, sq: Square) {
+        debug_assert!(self.board[sq.to_index()].is_none());
+        self.board[sq.to_index()] = Some(p);
+        self.pieces_bb[p.index()].set(sq);
+        self.colors_bb[p.color_of().index()].set(sq);
+    }
+
+    /// Removes the piece from the given square and returns it.
+    #[inline]
+    pub(crate) fn remove_piece(&mut self, sq: Square) -> Piece {
+        let p = self.board[sq.to_index()].take().expect("remove_piece on empty square");
+        self.pieces_bb[p.index()].clear(sq);
+        self.colors_bb[p.color_of().index()].clear(sq);
+        p
+    }
+
+    /// Moves the piece from one square to another empty square and returns it.
+    #[inline]
+    fn move_piece(&mut self, from: Square, to: Square) -> Piece {
+        let p = self.remove_piece(from);
+        self.put_piece(p, to);
+        p
+    }
+
+    /// Makes the given move which must be at least pseudo legal in this position.
+    /// The state needed to take the move back is pushed on the history stack.
+    ///
+    /// The en passant square is only set after a pawn double push when an enemy
+    /// pawn is able to capture on it so that otherwise equal positions have
+    /// equal keys.
+    pub fn do_move(&mut self, m: Move) {
+        let us = self.side_to_move;
+        let them = us.flip();
+        let from = m.from();
+        let to = m.to();
+        let p = self.piece_on(from).expect("do_move from empty square");
+        debug_assert_eq!(us, p.color_of());
+        let captured = match m.move_type() {
+            MoveType::EnPassant => Some(Piece::new(them, PieceType::Pawn)),
+            MoveType::Castling => None,
+            _ => self.piece_on(to),
+        };
+
+        self.history.push(Undo {
+            mv: m,
+            captured,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            key: self.key,
+        });
+
+        let mut key = self.key ^ zobrist::side(us) ^ zobrist::side(them);
+        if let Some(ep) = self.en_passant_capture() {
+            key ^= zobrist::en_passant(ep.file_of());
+        }
+        self.en_passant = None;
+        self.halfmove_clock += 1;
+
+        if m.move_type() == MoveType::Castling {
+            let (rook_from, rook_to) = castling_rook_squares(from, to);
+            let rook = self.move_piece(rook_from, rook_to);
+            self.move_piece(from, to);
+            key ^= zobrist::piece(p, from) ^ zobrist::piece(p, to)
+                ^ zobrist::piece(rook, rook_from) ^ zobrist::piece(rook, rook_to);
+        } else {
+            if let Some(c) = captured {
+                let capture_sq = match m.move_type() {
+                    MoveType::EnPassant => Square::from_file_rank(to.file_of(), from.rank_of()),
+                    _ => to,
+                };
+                self.remove_piece(capture_sq);
+                key ^= zobrist::piece(c, capture_sq);
+                self.halfmove_clock = 0;
+            }
+            self.remove_piece(from);
+            let placed = m.promotion().map_or(p, |pt| Piece::new(us, pt));
+            self.put_piece(placed, to);
+            key ^= zobrist::piece(p, from) ^ zobrist::piece(placed, to);
+
+            if p.type_of() == PieceType::Pawn {
+                self.halfmove_clock = 0;
+                if from.distance(to) == 2 {
+                    let ep = Square::from_file_rank(from.file_of(), Rank::Three.relative_to(us));
+                    if self.can_capture_en_passant(ep, them) {
+                        self.en_passant = Some(ep);
+                        key ^= zobrist::en_passant(ep.file_of());
+                    }
+                }
+            }
+        }
+
+        let lost = CASTLING_LOST[from.to_index()] | CASTLING_LOST[to.to_index()];
+        let castling_rights = self.castling_rights.remove(lost);
+        if castling_rights != self.castling_rights {
+            key ^= zobrist::castling(self.castling_rights) ^ zobrist::castling(castling_rights);
+            self.castling_rights = castling_rights;
+        }
+
+        if us == Color::Black {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = them;
+        self.key = key;
+
+        #[cfg(debug_assertions)]
+        self.assert_consistent();
+    }
+
+    /// Passes the right to move to the opponent without moving a piece. The null
+    /// move is taken back with `undo_move`.
+    pub fn do_null_move(&mut self) {
+        let us = self.side_to_move;
+        self.history.push(Undo {
+            mv: Move::NULL,
+            captured: None,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            key: self.key,
+        });
+
+        if let Some(ep) = self.en_passant_capture() {
+            self.key ^= zobrist::en_passant(ep.file_of());
+        }
+        self.en_passant = None;
+        self.key ^= zobrist::side(us) ^ zobrist::side(us.flip());
+        self.halfmove_clock += 1;
+        if us == Color::Black {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = us.flip();
+
+        #[cfg(debug_assertions)]
+        self.assert_consistent();
+    }
+
+    /// Takes back the last move or null move.
+    ///
+    /// # Panics
+    /// Panics if there is no move to take back.
+    pub fn undo_move(&mut self) {
+        let undo = self.history.pop().expect("undo_move without a move to take back");
+        let us = self.side_to_move.flip();
+        let m = undo.mv;
+
+        if !m.is_null() {
+            let from = m.from();
+            let to = m.to();
+            match m.move_type() {
+                MoveType::Castling => {
+                    let (rook_from, rook_to) = castling_rook_squares(from, to);
+                    self.move_piece(to, from);
+                    self.move_piece(rook_to, rook_from);
+                }
+                move_type => {
+                    let placed = self.remove_piece(to);
+                    let p = match move_type {
+                        MoveType::Promotion => Piece::new(us, PieceType::Pawn),
+                        _ => placed,
+                    };
+                    self.put_piece(p, from);
+                    if let Some(c) = undo.captured {
+                        let capture_sq = match move_type {
+                            MoveType::EnPassant => Square::from_file_rank(to.file_of(), from.rank_of()),
+                            _ => to,
+                        };
+                        self.put_piece(c, capture_sq);
+                    }
+                }
+            }
+        }
+
+        self.side_to_move = us;
+        self.castling_rights = undo.castling_rights;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.fullmove_number = undo.fullmove_number;
+        self.key = undo.key;
+
+        #[cfg(debug_assertions)]
+        self.assert_consistent();
+    }
+
+    /// Returns true if a pawn of the given color can capture en passant on the square.
+    #[inline]
+    fn can_capture_en_passant(&self, ep: Square, c: Color) -> bool {
+        !(attacks::pawn(c.flip(), ep) & self.pieces_of(c, PieceType::Pawn)).is_empty()
+    }
+
+    /// Computes the Zobrist key of the position from scratch.
+    fn compute_key(&self) -> Key {
+        let mut key = zobrist::side(self.side_to_move) ^ zobrist::castling(self.castling_rights);
+        if let Some(ep) = self.en_passant_capture() {
+            key ^= zobrist::en_passant(ep.file_of());
+        }
+        for sq in self.occupied() {
+            key ^= zobrist::piece(self.board[sq.to_index()].unwrap(), sq);
+        }
+        key
+    }
+
+    /// Recomputes the bitboards and the key from the mailbox and the castling rights,
+    /// en passant square and clocks from the mailbox and the last saved state and
+    /// panics if they differ from the incrementally updated ones.
+    #[cfg(debug_assertions)]
+    fn assert_consistent(&self) {
+        let mut pos = Position::empty();
+        for sq in SQUARES {
+            if let Some(p) = self.piece_on(sq) {
+                pos.put_piece(p, sq);
+            }
+        }
+        assert_eq!(pos.pieces_bb, self.pieces_bb, "piece bitboards out of sync in {}", self);
+        assert_eq!(pos.colors_bb, self.colors_bb, "color bitboards out of sync in {}", self);
+        assert_eq!(self.compute_key(), self.key, "zobrist key out of sync in {}", self);
+        assert_eq!(CastlingRights::NONE, self.castling_rights.remove(possible_castling_rights(self)),
+                   "castling rights without king or rook in {}", self);
+        if let Some(ep) = self.en_passant {
+            let us = self.side_to_move;
+            assert_eq!(Rank::Six.relative_to(us), ep.rank_of(), "en passant square on wrong rank in {}", self);
+        }
+        if let Some(last) = self.history.last() {
+            let m = last.mv;
+            let resets = !m.is_null()
+                && (last.captured.is_some()
+                    || m.move_type() == MoveType::Promotion
+                    || self.piece_on(m.to()).map(|p| p.type_of()) == Some(PieceType::Pawn));
+            let halfmove_clock = if resets { 0 } else { last.halfmove_clock + 1 };
+            assert_eq!(halfmove_clock, self.halfmove_clock, "halfmove clock out of sync in {}", self);
+            let fullmove_number = last.fullmove_number + (self.side_to_move == Color::White) as u32;
+            assert_eq!(fullmove_number, self.fullmove_number, "fullmove number out of sync in {}", self);
+        }
+    }
+
+    /// Returns the FEN of the position.
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::with_capacity(90);
+        for r in RANKS.iter().rev() {
+            let mut empty = 0;
+            for f in FILES {
+                match self.piece_on(Square::from_file_rank(f, *r)) {
+                    Some(p) => {
+                        if empty > 0 {
+                            fen.push(char::from(b'0' + empty));
+                            empty = 0;
+                        }
+                        fen.push(p.to_char());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push(char::from(b'0' + empty));
+            }
+            if *r != Rank::One {
+                fen.push('/');
+            }
+        }
+        let en_passant = match self.en_passant {
+            Some(sq) => sq.to_string(),
+            None => "-".to_string(),
+        };
+        format!("{} {} {} {} {} {}", fen, self.side_to_move, self.castling_rights, en_passant,
+                self.halfmove_clock, self.fullmove_number)
+    }
+
+}
+
+impl Default for Position {
+    fn default() -> Self { Position::new() }
+}
+
+/// Splits the FEN into its fields and returns them with their character offsets.
+fn fen_fields(fen: &str) -> Vec<(usize, &str)> {
+    let mut fields = Vec::new();
+    let mut start = None;
+    for (i, (byte, c)) in fen.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (true, Some((offset, from))) => {
+                fields.push((offset, &fen[from..byte]));
+                start = None;
+            }
+            (false, None) => start = Some((i, byte)),
+            _ => {}
+        }
+    }
+    if let Some((offset, from)) = start {
+        fields.push((offset, &fen[from..]));
+    }
+    fields
+}
+
+/// Returns the castling rights for which king and rook stand on their start squares.
+/// The castling rights of a FEN must be a subset of these.
+fn possible_castling_rights(pos: &Position) -> CastlingRights {
+    let mut rights = CastlingRights::NONE;
+    for c in COLORS {
+        let rank = c.back_rank();
+        if pos.piece_on(Square::from_file_rank(File::E, rank)) != Some(Piece::new(c, PieceType::King)) {
+            continue;
+        }
+        let rook = Some(Piece::new(c, PieceType::Rook));
+        if pos.piece_on(Square::from_file_rank(File::H, rank)) == rook {
+            rights |= CastlingRights::king_side(c);
+        }
+        if pos.piece_on(Square::from_file_rank(File::A, rank)) == rook {
+            rights |= CastlingRights::queen_side(c);
+        }
+    }
+    rights
+}
+
+/// Parses a position from FEN. The halfmove clock and fullmove number may be
+/// omitted and default to 0 and 1.
+impl FromStr for Position {
+    type Err = Error;
+    fn from_str(fen: &str) -> Result<Self, Self::Err> {
+        let invalid = |field, offset| Error::InvalidFen { field, input: fen.to_string(), offset };
+        let fields = fen_fields(fen);
+        let field = |n: usize, f: FenField| fields.get(n).copied().ok_or_else(|| invalid(f, fen.chars().count()));
+        let mut pos = Position::empty();
+
+        // piece placement
+        let (offset, placement) = field(0, FenField::Placement)?;
+        let mut rank = 7;
+        let mut file = 0;
+        let mut after_digit = false;
+        for (i, c) in placement.chars().enumerate() {
+            let error = || invalid(FenField::Placement, offset + i);
+            match c {
+                '/' => {
+                    if file != 8 || rank == 0 {
+                        return Err(error());
+                    }
+                    rank -= 1;
+                    file = 0;
+                    after_digit = false;
+                }
+                '1'..='8' => {
+                    // consecutive digits like "44" would not round trip
+                    file += c as usize - '0' as usize;
+                    if file > 8 || after_digit {
+                        return Err(error());
+                    }
+                    after_digit = true;
+                }
+                _ => {
+                    let p = Piece::from_str(c.encode_utf8(&mut [0; 4])).map_err(|_| error())?;
+                    if file > 7 || (p.type_of() == PieceType::Pawn && (rank == 0 || rank == 7)) {
+                        return Err(error());
+                    }
+                    pos.put_piece(p, Square::from_file_rank(FILES[file], RANKS[rank]));
+                    file += 1;
+                    after_digit = false;
+                }
+            }
+        }
+        if rank != 0 || file != 8 {
+            return Err(invalid(FenField::Placement, offset + placement.chars().count()));
+        }
+        if pos.pieces(Piece::WhiteKing).popcount() != 1 || pos.pieces(Piece::BlackKing).popcount() != 1 {
+            return Err(invalid(FenField::Placement, offset));
+        }
+
+        // side to move
+        let (offset, side) = field(1, FenField::SideToMove)?;
+        pos.side_to_move = Color::from_str(side).map_err(|e| invalid(FenField::SideToMove, offset + e.offset()))?;
+
+        // castling rights
+        let (offset, castling) = field(2, FenField::CastlingRights)?;
+        pos.castling_rights = CastlingRights::from_str(castling)
+            .map_err(|e| invalid(FenField::CastlingRights, offset + e.offset()))?;
+        if pos.castling_rights.remove(possible_castling_rights(&pos)) != CastlingRights::NONE {
+            return Err(invalid(FenField::CastlingRights, offset));
+        }
+
+        // en passant square which must be behind a pawn which just moved two squares,
+        // so the pawn stands in front of it and it and the square it came from are empty
+        let (offset, en_passant) = field(3, FenField::EnPassant)?;
+        if en_passant != "-" {
+            let sq = Square::from_str(en_passant).map_err(|e| invalid(FenField::EnPassant, offset + e.offset()))?;
+            let us = pos.side_to_move;
+            if sq.rank_of() != Rank::Six.relative_to(us) {
+                return Err(invalid(FenField::EnPassant, offset + 1));
+            }
+            let pawn = sq.offset(us.flip().forward()).unwrap();
+            let origin = sq.offset(us.forward()).unwrap();
+            if pos.piece_on(pawn) != Some(Piece::new(us.flip(), PieceType::Pawn))
+                || pos.piece_on(sq).is_some()
+                || pos.piece_on(origin).is_some() {
+                return Err(invalid(FenField::EnPassant, offset));
+            }
+            // kept as given for the round trip, compute_key leaves it out if no pawn can capture
+            pos.en_passant = Some(sq);
+        }
+
+        // move clocks
+        if let Some((offset, halfmove)) = fields.get(4) {
+            pos.halfmove_clock = halfmove.parse().map_err(|_| invalid(FenField::HalfmoveClock, *offset))?;
+        }
+        if let Some((offset, fullmove)) = fields.get(5) {
+            pos.fullmove_number = match fullmove.parse() {
+                Ok(n) if n > 0 => n,
+                _ => return Err(invalid(FenField::FullmoveNumber, *offset)),
+            };
+        }
+        if let Some((offset, _)) = fields.get(6) {
+            return Err(invalid(FenField::FullmoveNumber, *offset));
+        }
+
+        pos.key = pos.compute_key();
+        Ok(pos)
+    }
+}
+
+/// Prints the position as FEN.
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_fen())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FENS: [&str; 6] = [
+        START_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbqkb1r/pp1p1ppp/5n2/2pPp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 4",
+        "4k3/8/8/8/8/8/8/4K3 b - - 49 120",
+    ];
+
+    #[test]
+    fn test_start_position() {
+        let pos = Position::new();
+        assert_eq!(Color::White, pos.side_to_move());
+        assert_eq!(CastlingRights::ANY, pos.castling_rights());
+        assert_eq!(None, pos.en_passant());
+        assert_eq!(0, pos.halfmove_clock());
+        assert_eq!(1, pos.fullmove_number());
+        assert_eq!(Some(Piece::WhiteKing), pos.piece_on(Square::E1));
+        assert_eq!(Some(Piece::BlackQueen), pos.piece_on(Square::D8));
+        assert_eq!(None, pos.piece_on(Square::E4));
+        assert_eq!(Bitboard::RANK_2, pos.pieces(Piece::WhitePawn));
+        assert_eq!(Bitboard::RANK_7 | Bitboard::RANK_8, pos.occupied_by(Color::Black));
+        assert_eq!(32, pos.occupied().popcount());
+        assert_eq!(Square::E8, pos.king_square(Color::Black));
+        assert_eq!(pos, Position::default());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for fen in FENS {
+            let pos = Position::from_str(fen).unwrap();
+            assert_eq!(fen, pos.to_fen());
+            assert_eq!(fen, format!("{}", pos));
+        }
+    }
+
+    #[test]
+    fn test_bitboards_match_mailbox() {
+        for fen in FENS {
+            let pos = Position::from_str(fen).unwrap();
+            for sq in SQUARES {
+                match pos.piece_on(sq) {
+                    Some(p) => {
+                        assert!(pos.pieces(p).has(sq));
+                        assert!(pos.occupied_by(p.color_of()).has(sq));
+                    }
+                    None => assert!(!pos.occupied().has(sq)),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_optional_clocks() {
+        let pos = Position::from_str("4k3/8/8/8/8/8/8/4K3 w - -").unwrap();
+        assert_eq!(0, pos.halfmove_clock());
+        assert_eq!(1, pos.fullmove_number());
+        assert_eq!("4k3/8/8/8/8/8/8/4K3 w - - 0 1", pos.to_fen());
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |fen: &str, field, offset| Err(Error::InvalidFen { field, input: fen.to_string(), offset });
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1";
+        assert_eq!(error(fen, FenField::SideToMove, 44), Position::from_str(fen));
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQxq - 0 1";
+        assert_eq!(error(fen, FenField::CastlingRights, 48), Position::from_str(fen));
+        // castling rights require king and rook on their start squares
+        let fen = "4k3/8/8/8/8/8/8/4K1R1 w K - 0 1";
+        assert_eq!(error(fen, FenField::CastlingRights, 24), Position::from_str(fen));
+        let fen = "rnbqkbnr/ppppXppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(error(fen, FenField::Placement, 13), Position::from_str(fen));
+        let fen = "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(error(fen, FenField::Placement, 18), Position::from_str(fen));
+        let fen = "rnbqkbnr/pppppppp/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(error(fen, FenField::Placement, 41), Position::from_str(fen));
+        let fen = "rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(error(fen, FenField::Placement, 0), Position::from_str(fen));
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1";
+        assert_eq!(error(fen, FenField::EnPassant, 52), Position::from_str(fen));
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e9 0 1";
+        assert_eq!(error(fen, FenField::EnPassant, 52), Position::from_str(fen));
+        // no black pawn in front of the en passant square
+        let fen = "4k3/8/8/3P4/8/8/8/4K3 w - e6 0 1";
+        assert_eq!(error(fen, FenField::EnPassant, 26), Position::from_str(fen));
+        // the square the pawn came from is occupied
+        let fen = "4k3/4r3/8/4pP2/8/8/8/4K3 w - e6 0 1";
+        assert_eq!(error(fen, FenField::EnPassant, 29), Position::from_str(fen));
+        // offsets count characters, the ideographic space takes three bytes
+        let fen = "4k3/8/8/8/8/8/8/4K3\u{3000}x - - 0 1";
+        assert_eq!(error(fen, FenField::SideToMove, 20), Position::from_str(fen));
+        let fen = "4k3/8/8/8/8/8/8/4K3\u{3000}w\u{3000}Kx - 0 1";
+        assert_eq!(error(fen, FenField::CastlingRights, 23), Position::from_str(fen));
+        let fen = "4k3/8/8/8/8/8/8/4K3\u{3000}w";
+        assert_eq!(error(fen, FenField::CastlingRights, 21), Position::from_str(fen));
+        let fen = "rnbqkbnr/pppppppp/44/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(error(fen, FenField::Placement, 19), Position::from_str(fen));
+        let fen = "rnbqkbnP/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(error(fen, FenField::Placement, 7), Position::from_str(fen));
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNp w KQkq - 0 1";
+        assert_eq!(error(fen, FenField::Placement, 42), Position::from_str(fen));
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1";
+        assert_eq!(error(fen, FenField::HalfmoveClock, 53), Position::from_str(fen));
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0";
+        assert_eq!(error(fen, FenField::FullmoveNumber, 55), Position::from_str(fen));
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w";
+        assert_eq!(error(fen, FenField::CastlingRights, 45), Position::from_str(fen));
+        assert_eq!(error("", FenField::Placement, 0), Position::from_str(""));
+    }
+
+    /// Makes the moves on the position and checks each resulting FEN and key
+    /// against a position parsed from the expected FEN, then takes all moves back.
+    fn check_moves(fen: &str, moves: &[(Move, &str)]) {
+        let mut pos = Position::from_str(fen).unwrap();
+        let start = pos.clone();
+        for (m, expected) in moves {
+            pos.do_move(*m);
+            assert_eq!(*expected, pos.to_fen(), "after {}", m);
+            assert_eq!(Position::from_str(expected).unwrap().key(), pos.key(), "after {}", m);
+            assert_eq!(Some(*m), pos.last_move());
+        }
+        assert_eq!(moves.len(), pos.ply());
+        for _ in moves {
+            pos.undo_move();
+        }
+        assert_eq!(start, pos);
+    }
+
+    #[test]
+    fn test_do_undo_normal_and_capture() {
+        check_moves(START_FEN, &[
+            (Move::new(Square::G1, Square::F3), "rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1"),
+            (Move::new(Square::E7, Square::E5), "rnbqkbnr/pppp1ppp/8/4p3/8/5N2/PPPPPPPP/RNBQKB1R w KQkq - 0 2"),
+            (Move::new(Square::F3, Square::E5), "rnbqkbnr/pppp1ppp/8/4N3/8/8/PPPPPPPP/RNBQKB1R b KQkq - 0 2"),
+            (Move::new(Square::D8, Square::H4), "rnb1kbnr/pppp1ppp/8/4N3/7q/8/PPPPPPPP/RNBQKB1R w KQkq - 1 3"),
+        ]);
+    }
+
+    #[test]
+    fn test_do_undo_en_passant() {
+        check_moves("rnbqkbnr/ppp1pppp/8/8/3p4/8/PPPPPPPP/RNBQKBNR w KQkq - 0 3", &[
+            (Move::new(Square::A2, Square::A4), "rnbqkbnr/ppp1pppp/8/8/P2p4/8/1PPPPPPP/RNBQKBNR b KQkq - 0 3"),
+            (Move::new(Square::A7, Square::A6), "rnbqkbnr/1pp1pppp/p7/8/P2p4/8/1PPPPPPP/RNBQKBNR w KQkq - 0 4"),
+            (Move::new(Square::E2, Square::E4), "rnbqkbnr/1pp1pppp/p7/8/P2pP3/8/1PPP1PPP/RNBQKBNR b KQkq e3 0 4"),
+            (Move::new_en_passant(Square::D4, Square::E3), "rnbqkbnr/1pp1pppp/p7/8/P7/4p3/1PPP1PPP/RNBQKBNR w KQkq - 0 5"),
+        ]);
+    }
+
+    #[test]
+    fn test_do_undo_castling() {
+        check_moves("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", &[
+            (Move::new_castling(Square::E1, Square::G1), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1"),
+            (Move::new_castling(Square::E8, Square::C8), "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2"),
+        ]);
+        check_moves("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", &[
+            (Move::new(Square::A1, Square::A8), "R3k2r/8/8/8/8/8/8/4K2R b Kk - 0 1"),
+            (Move::new(Square::E8, Square::E7), "R6r/4k3/8/8/8/8/8/4K2R w K - 1 2"),
+            (Move::new(Square::H1, Square::H8), "R6R/4k3/8/8/8/8/8/4K3 b - - 0 2"),
+        ]);
+    }
+
+    #[test]
+    fn test_do_undo_promotion() {
+        check_moves("r3k3/1P6/8/8/8/8/6p1/4K2R w K - 0 1", &[
+            (Move::new_promotion(Square::B7, Square::A8, PieceType::Queen), "Q3k3/8/8/8/8/8/6p1/4K2R b K - 0 1"),
+            (Move::new_promotion(Square::G2, Square::H1, PieceType::Knight), "Q3k3/8/8/8/8/8/8/4K2n w - - 0 2"),
+        ]);
+    }
+
+    #[test]
+    fn test_do_undo_null_move() {
+        let mut pos = Position::from_str("rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 2").unwrap();
+        let start = pos.clone();
+        pos.do_null_move();
+        assert_eq!("rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR w KQkq - 1 3", pos.to_fen());
+        assert_eq!(Position::from_str(&pos.to_fen()).unwrap().key(), pos.key());
+        assert_eq!(Some(Move::NULL), pos.last_move());
+        pos.undo_move();
+        assert_eq!(start, pos);
+    }
+
+    #[test]
+    fn test_en_passant_round_trip() {
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+        let pos = Position::from_str(fen).unwrap();
+        assert_eq!(Some(Square::E3), pos.en_passant());
+        assert_eq!(None, pos.en_passant_capture());
+        assert_eq!(fen, pos.to_fen());
+        assert_eq!(fen, pos.to_string());
+    }
+
+    #[test]
+    fn test_en_passant_key() {
+        // no black pawn can capture on e3, so the square is not part of the key as in do_move
+        let pos = Position::from_str("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+        assert_eq!(Some(Square::E3), pos.en_passant());
+        let mut played = Position::from_str("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        played.do_move(Move::new(Square::E2, Square::E4));
+        assert_eq!(pos.key(), played.key());
+        let captured = Position::from_str("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+        let quiet = Position::from_str("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").unwrap();
+        assert_ne!(captured.key(), quiet.key());
+    }
+
+    #[test]
+    fn test_key_transposition() {
+        let mut a = Position::new();
+        let mut b = Position::new();
+        for m in [Move::new(Square::G1, Square::F3), Move::new(Square::G8, Square::F6),
+                  Move::new(Square::B1, Square::C3), Move::new(Square::B8, Square::C6)] {
+            a.do_move(m);
+        }
+        for m in [Move::new(Square::B1, Square::C3), Move::new(Square::B8, Square::C6),
+                  Move::new(Square::G1, Square::F3), Move::new(Square::G8, Square::F6)] {
+            b.do_move(m);
+        }
+        assert_eq!(a.key(), b.key());
+        assert_ne!(Position::new().key(), a.key());
+    }
+
+}
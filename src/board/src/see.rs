@@ -0,0 +1,213 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::Position;
+use types::attacks;
+use types::*;
+
+/// Longest possible exchange on one square (all 32 pieces) plus one
+const MAX_SWAPS: usize = 33;
+
+impl Position {
+
+    /// Returns the static exchange evaluation of the move: the material balance
+    /// for the side to move after all captures on the destination square, where
+    /// each side always recaptures with its least valuable piece and may stop
+    /// capturing when that is better.
+    ///
+    /// Sliders behind a capturing piece join the exchange (x-rays), promotions
+    /// count the gain of the promoted piece and en passant captures the pawn
+    /// behind the destination. Pins are not taken into account, a king only
+    /// captures if the square is no longer defended. Castling evaluates to 0.
+    pub fn see(&self, m: Move) -> Value {
+        if m.move_type() == MoveType::Castling {
+            return 0;
+        }
+        let from = m.from();
+        let to = m.to();
+        let mut stm = self.side_to_move();
+        let mut occupied = self.occupied();
+        if m.move_type() == MoveType::EnPassant {
+            occupied ^= Square::from_file_rank(to.file_of(), from.rank_of()).bitboard();
+        }
+        let mut gain = [0 as Value; MAX_SWAPS];
+        // value of the piece which now stands on the destination square
+        let mut on_square;
+        (gain[0], on_square) = self.first_swap(m);
+
+        let bishops = self.pieces_by_type(PieceType::Bishop) | self.pieces_by_type(PieceType::Queen);
+        let rooks = self.pieces_by_type(PieceType::Rook) | self.pieces_by_type(PieceType::Queen);
+        let mut attackers = self.attackers_to(to, occupied);
+        let mut capturer = from;
+        let mut depth = 1;
+        loop {
+            // remove the last capturer and add the sliders it was hiding
+            occupied ^= capturer.bitboard();
+            attackers |= (attacks::bishop(to, occupied) & bishops) | (attacks::rook(to, occupied) & rooks);
+            attackers &= occupied;
+
+            stm = stm.flip();
+            let stm_attackers = attackers & self.occupied_by(stm);
+            let Some((pt, sq)) = self.least_valuable(stm, stm_attackers) else { break };
+            if pt == PieceType::King && !(attackers & self.occupied_by(stm.flip())).is_empty() {
+                break;
+            }
+
+            gain[depth] = on_square - gain[depth - 1];
+            on_square = pt.value();
+            if pt == PieceType::Pawn && to.rank_of() == stm.promotion_rank() {
+                gain[depth] += PieceType::Queen.value() - PieceType::Pawn.value();
+                on_square = PieceType::Queen.value();
+            }
+            capturer = sq;
+            depth += 1;
+        }
+
+        // each side chooses between stopping and the result of capturing further
+        for d in (1..depth).rev() {
+            gain[d - 1] = -(-gain[d - 1]).max(gain[d]);
+        }
+        gain[0]
+    }
+
+    /// Returns true if the static exchange evaluation of the move is at least the
+    /// threshold. Most captures are decided by bounds on the first two captures
+    /// without resolving the whole exchange: the side to move never gets more
+    /// than the first capture wins, as the opponent may always stop, and never
+    /// less than that minus the moved piece, as it may stop after the recapture.
+    /// The lower bound does not hold if the opponent recaptures with promotion.
+    pub fn see_ge(&self, m: Move, threshold: Value) -> bool {
+        if m.move_type() == MoveType::Castling {
+            return 0 >= threshold;
+        }
+        let (first, on_square) = self.first_swap(m);
+        if first < threshold {
+            return false;
+        }
+        let them = self.side_to_move().flip();
+        if first - on_square >= threshold && m.to().rank_of() != them.promotion_rank() {
+            return true;
+        }
+        self.see(m) >= threshold
+    }
+
+    /// Returns the material won by the move itself including the promotion gain and
+    /// the value of the piece standing on the destination square afterwards.
+    fn first_swap(&self, m: Move) -> (Value, Value) {
+        let captured = match m.move_type() {
+            MoveType::EnPassant => PieceType::Pawn.value(),
+            _ => self.piece_on(m.to()).map_or(0, |p| p.value()),
+        };
+        match m.promotion() {
+            Some(pt) => (captured + pt.value() - PieceType::Pawn.value(), pt.value()),
+            None => (captured, self.piece_on(m.from()).expect("see of a move from an empty square").value()),
+        }
+    }
+
+    /// Returns the least valuable of the given pieces of the given color and its square.
+    fn least_valuable(&self, c: Color, candidates: Bitboard) -> Option<(PieceType, Square)> {
+        PIECE_TYPES.iter().find_map(|pt| {
+            let bb = candidates & self.pieces_of(c, *pt);
+            if bb.is_empty() { None } else { Some((*pt, bb.lsb())) }
+        })
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn see(fen: &str, uci: &str) -> Value {
+        let pos = Position::from_str(fen).unwrap();
+        let m = *pos.legal_moves().iter().find(|m| m.to_string() == uci).expect(uci);
+        let value = pos.see(m);
+        for threshold in [-2000, -900, -500, -100, 0, 100, 500, 900, 2000, value - 1, value, value + 1] {
+            assert_eq!(value >= threshold, pos.see_ge(m, threshold), "{} {} {}", fen, uci, threshold);
+        }
+        value
+    }
+
+    #[test]
+    fn test_see_simple() {
+        // undefended pawn
+        assert_eq!(100, see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"));
+        // knight takes a pawn defended by a knight and a long battery behind
+        assert_eq!(-220, see("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "d3e5"));
+        // quiet move onto a square attacked by a pawn
+        assert_eq!(-900, see("4k3/8/2p5/8/8/8/8/3QK3 w - - 0 1", "d1d5"));
+        // castling never loses material
+        assert_eq!(0, see("4k3/8/8/8/8/8/8/4K2R w K - 0 1", "e1g1"));
+    }
+
+    #[test]
+    fn test_see_x_rays() {
+        // the rook behind the capturing rook wins the exchange
+        assert_eq!(100, see("4k3/4r3/8/4p3/8/8/4R3/4R1K1 w - - 0 1", "e2e5"));
+        // a queen behind the bishop recaptures the knight through d4
+        assert_eq!(90, see("4k3/3n4/8/4p3/3B4/2Q5/8/4K3 w - - 0 1", "d4e5"));
+        assert_eq!(-230, see("4k3/3n4/8/4p3/3B4/8/8/4K3 w - - 0 1", "d4e5"));
+        // without the second rook the exchange loses
+        assert_eq!(-400, see("4k3/4r3/8/4p3/8/8/4R3/4K3 w - - 0 1", "e2e5"));
+        // the black rook behind the recapturing rook
+        assert_eq!(-400, see("4k3/4r3/4r3/4p3/8/8/4R3/4R1K1 w - - 0 1", "e2e5"));
+    }
+
+    #[test]
+    fn test_see_king() {
+        // the king may recapture the undefended rook
+        assert_eq!(-300, see("3rk3/8/8/8/8/8/3p4/3QK3 w - - 0 1", "d1d2"));
+        // but not when the square is still defended
+        assert_eq!(-800, see("3rk3/3r4/8/8/8/8/3p4/3QK3 w - - 0 1", "d1d2"));
+    }
+
+    #[test]
+    fn test_see_promotion_and_en_passant() {
+        assert_eq!(800, see("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q"));
+        assert_eq!(220, see("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8n"));
+        // the promoted queen is lost to the rook, only the pawn is gone
+        assert_eq!(-100, see("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q"));
+        // capturing the rook with promotion and losing the queen to the knight
+        assert_eq!(400, see("1r2k3/P7/2n5/8/8/8/8/4K3 w - - 0 1", "a7b8q"));
+        // the knight recaptures the queen and is recaptured by the promoting pawn
+        assert_eq!(500, see("1r2k3/P7/2n5/8/8/8/8/1Q2K3 w - - 0 1", "b1b8"));
+        assert_eq!(100, see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2", "e5d6"));
+        // the rook behind the captured pawn defends the en passant square
+        assert_eq!(0, see("3rk3/8/8/3pP3/8/8/8/4K3 w - d6 0 2", "e5d6"));
+    }
+
+    #[test]
+    fn test_see_ge_matches_see() {
+        for fen in ["r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+                    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10"] {
+            let pos = Position::from_str(fen).unwrap();
+            for m in pos.legal_moves().iter() {
+                let value = pos.see(*m);
+                for threshold in (-1000..=1000).step_by(10) {
+                    assert_eq!(value >= threshold, pos.see_ge(*m, threshold), "{} {} {}", fen, m, threshold);
+                }
+            }
+        }
+    }
+
+}
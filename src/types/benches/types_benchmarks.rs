@@ -22,7 +22,8 @@
 #![allow(unused_imports)]
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use types::{Color, File, Rank, Square};
+use std::str::FromStr;
+use types::{attacks, Bitboard, Color, File, Move, Rank, Square};
 
 fn flip(color: &Color) -> Color {
     match *color {
@@ -39,13 +40,87 @@ fn flip2(color: &Color) -> Color {
     }
 }
 
+/// The square parser before parse_bytes: allocates in to_lowercase() and
+/// ignores trailing input. Kept to compare against.
+fn allocating_square(s: &str) -> Option<Square> {
+    if s.len() < 2 {
+        return None;
+    }
+    let file = match s[0..1].to_lowercase().chars().next().unwrap() {
+        c @ 'a'..='h' => File::try_from_index(c as usize - 'a' as usize)?,
+        _ => return None,
+    };
+    let rank = match s[1..2].chars().next().unwrap() {
+        c @ '1'..='8' => Rank::try_from_index(c as usize - '1' as usize)?,
+        _ => return None,
+    };
+    Some(Square::from_file_rank(file, rank))
+}
+
+/// A move parser built on allocating_square.
+fn allocating_move(s: &str) -> Option<Move> {
+    if s.len() < 4 {
+        return None;
+    }
+    Some(Move::new(allocating_square(&s[0..2])?, allocating_square(&s[2..4])?))
+}
+
 
 fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("bench match", |b| b.iter(|| black_box(flip(&Color::Black))));
     c.bench_function("bench if", |b| b.iter(|| black_box(flip2(&Color::Black))));
 
-    c.bench_function("bench from1", |b| b.iter(|| black_box(File::from_index(4))));
-    c.bench_function("bench from2", |b| b.iter(|| black_box(File::from_index(4))));
+    c.bench_function("bench from1", |b| b.iter(|| black_box(File::wrapping_from_index(4))));
+    c.bench_function("bench from2", |b| b.iter(|| black_box(File::try_from_index(4))));
+
+    c.bench_function("square allocating", |b| b.iter(|| black_box(allocating_square(black_box("e4")))));
+    c.bench_function("square from_str", |b| b.iter(|| black_box(Square::from_str(black_box("e4")))));
+
+    // the moves of a UCI "position startpos moves ..." command
+    let line = "position startpos moves e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5a4 g8f6 e1g1 f8e7 \
+                f1e1 b7b5 a4b3 d7d6 c2c3 e8g8 h2h3 c6b8 d2d4 b8d7";
+    c.bench_function("uci moves allocating", |b| b.iter(|| {
+        for m in black_box(line).split_whitespace().skip(3) {
+            black_box(allocating_move(m).unwrap());
+        }
+    }));
+    c.bench_function("uci moves from_str", |b| b.iter(|| {
+        for m in black_box(line).split_whitespace().skip(3) {
+            black_box(Move::from_str(m).unwrap());
+        }
+    }));
+
+    let occupied = Bitboard(0x2C_4E10_0C10_C0F3);
+    c.bench_function("rook magic", |b| b.iter(|| {
+        for sq in Square::iter() {
+            black_box(attacks::rook_magic(sq, black_box(occupied)));
+        }
+    }));
+    c.bench_function("rook default backend", |b| b.iter(|| {
+        for sq in Square::iter() {
+            black_box(attacks::rook(sq, black_box(occupied)));
+        }
+    }));
+    c.bench_function("rook slow", |b| b.iter(|| {
+        for sq in Square::iter() {
+            black_box(attacks::rook_slow(sq, black_box(occupied)));
+        }
+    }));
+    c.bench_function("bishop magic", |b| b.iter(|| {
+        for sq in Square::iter() {
+            black_box(attacks::bishop_magic(sq, black_box(occupied)));
+        }
+    }));
+    c.bench_function("bishop default backend", |b| b.iter(|| {
+        for sq in Square::iter() {
+            black_box(attacks::bishop(sq, black_box(occupied)));
+        }
+    }));
+    c.bench_function("bishop slow", |b| b.iter(|| {
+        for sq in Square::iter() {
+            black_box(attacks::bishop_slow(sq, black_box(occupied)));
+        }
+    }));
 }
 
 criterion_group!(benches, criterion_benchmark);
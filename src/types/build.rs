@@ -0,0 +1,176 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Searches magic numbers for the rook and bishop attack tables and writes
+//! the magics together with the filled attack tables to OUT_DIR/magics.rs
+//! which is included by src/attacks.rs. The search uses a fixed seed so the
+//! generated tables are the same for every build.
+//! With the "pext" feature additional tables indexed by PEXT are written.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const ROOK_DIRECTIONS: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+
+/// xorshift64* pseudo random number generator
+struct Prng(u64);
+
+impl Prng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(2_685_821_657_736_338_717)
+    }
+
+    /// random number with only few bits set which makes good magic candidates
+    fn sparse(&mut self) -> u64 { self.next() & self.next() & self.next() }
+}
+
+/// Walks from the square into all directions until the edge or a blocker.
+/// With `mask` set the last square before the edge is excluded.
+fn sliding(sq: i32, occupied: u64, directions: &[(i32, i32); 4], mask: bool) -> u64 {
+    let mut bb = 0;
+    for (df, dr) in directions {
+        let (mut f, mut r) = (sq % 8 + df, sq / 8 + dr);
+        while (0..8).contains(&f) && (0..8).contains(&r) {
+            if mask && !((0..8).contains(&(f + df)) && (0..8).contains(&(r + dr))) {
+                break;
+            }
+            bb |= 1 << (r * 8 + f);
+            if occupied & (1 << (r * 8 + f)) != 0 {
+                break;
+            }
+            f += df;
+            r += dr;
+        }
+    }
+    bb
+}
+
+/// Returns all subsets of the mask together with the attacks for them.
+fn occupancies(sq: i32, mask: u64, directions: &[(i32, i32); 4]) -> Vec<(u64, u64)> {
+    let mut result = Vec::new();
+    // enumerate all subsets of the mask (Carry-Rippler)
+    let mut occupied: u64 = 0;
+    loop {
+        result.push((occupied, sliding(sq, occupied, directions, false)));
+        occupied = occupied.wrapping_sub(mask) & mask;
+        if occupied == 0 {
+            break;
+        }
+    }
+    result
+}
+
+fn find_magic(mask: u64, occupancies: &[(u64, u64)], prng: &mut Prng) -> u64 {
+    let bits = mask.count_ones();
+    let mut used = vec![0u64; 1 << bits];
+    let mut epoch = vec![0u32; 1 << bits];
+    let mut tries = 0;
+    loop {
+        let magic = prng.sparse();
+        if (mask.wrapping_mul(magic) >> 56).count_ones() < 6 {
+            continue;
+        }
+        tries += 1;
+        let mut ok = true;
+        for (occupied, attacks) in occupancies {
+            let index = (occupied.wrapping_mul(magic) >> (64 - bits)) as usize;
+            if epoch[index] < tries {
+                epoch[index] = tries;
+                used[index] = *attacks;
+            } else if used[index] != *attacks {
+                ok = false;
+                break;
+            }
+        }
+        if ok {
+            return magic;
+        }
+    }
+}
+
+/// Software version of the BMI2 PEXT instruction: gathers the bits of `value`
+/// selected by `mask` into the low bits of the result.
+fn pext(value: u64, mut mask: u64) -> u64 {
+    let mut result = 0;
+    let mut bit = 0;
+    while mask != 0 {
+        if value & mask & mask.wrapping_neg() != 0 {
+            result |= 1 << bit;
+        }
+        mask &= mask - 1;
+        bit += 1;
+    }
+    result
+}
+
+fn write_table(out: &mut String, name: &str, table: &[u64]) {
+    out.push_str(&format!("static {}: [Bitboard; {}] = [\n", name, table.len()));
+    for chunk in table.chunks(8) {
+        let line: Vec<String> = chunk.iter().map(|a| format!("Bitboard({:#x})", a)).collect();
+        out.push_str(&format!("    {},\n", line.join(", ")));
+    }
+    out.push_str("];\n");
+}
+
+/// Writes the magic entries NAME_MAGICS and the attack table NAME_ATTACKS.
+/// With `with_pext` the table NAME_PEXT_ATTACKS is written as well which uses
+/// the same offsets but is indexed by PEXT of the occupancy and the mask.
+fn write_tables(out: &mut String, name: &str, directions: &[(i32, i32); 4], prng: &mut Prng, with_pext: bool) {
+    let mut magics = String::new();
+    let mut table: Vec<u64> = Vec::new();
+    let mut pext_table: Vec<u64> = Vec::new();
+    for sq in 0..64 {
+        let mask = sliding(sq, 0, directions, true);
+        let bits = mask.count_ones();
+        let occupancies = occupancies(sq, mask, directions);
+        let magic = find_magic(mask, &occupancies, prng);
+        let offset = table.len();
+        magics.push_str(&format!("    Magic {{ mask: Bitboard({:#x}), magic: {:#x}, shift: {}, offset: {} }},\n",
+                                 mask, magic, 64 - bits, offset));
+        table.resize(offset + (1 << bits), 0);
+        pext_table.resize(offset + (1 << bits), 0);
+        for (occupied, attacks) in occupancies {
+            table[offset + (occupied.wrapping_mul(magic) >> (64 - bits)) as usize] = attacks;
+            pext_table[offset + pext(occupied, mask) as usize] = attacks;
+        }
+    }
+    out.push_str(&format!("static {}_MAGICS: [Magic; 64] = [\n{}];\n", name, magics));
+    write_table(out, &format!("{}_ATTACKS", name), &table);
+    if with_pext {
+        write_table(out, &format!("{}_PEXT_ATTACKS", name), &pext_table);
+    }
+}
+
+fn main() {
+    let mut prng = Prng(728_446_701_991_534_743);
+    let mut out = String::from("// generated by build.rs - do not edit\n");
+    let with_pext = env::var_os("CARGO_FEATURE_PEXT").is_some();
+    write_tables(&mut out, "ROOK", &ROOK_DIRECTIONS, &mut prng, with_pext);
+    write_tables(&mut out, "BISHOP", &BISHOP_DIRECTIONS, &mut prng, with_pext);
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("magics.rs");
+    fs::write(path, out).unwrap();
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,425 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Precomputed attack sets of all pieces. Knights, kings and pawn captures use
+//! simple per-square tables. Rooks, bishops and queens use fancy magic bitboards
+//! whose magic numbers and attack tables are generated by build.rs so there is
+//! no start-up cost.
+//! With the "pext" feature on x86_64 the slider tables are indexed by the BMI2
+//! PEXT instruction instead if the CPU supports it (checked at runtime).
+//! Building with `-C target-feature=+bmi2` (or a target-cpu which has it) turns
+//! the runtime check into a constant and lets the lookups be inlined.
+//! set_backend() forces the magic backend, e.g. to compare both in tests.
+
+use crate::Bitboard;
+use crate::Color;
+use crate::Direction;
+use crate::Piece;
+use crate::PieceType;
+use crate::Square;
+use crate::BISHOP_DIRECTIONS;
+use crate::COLORS;
+use crate::COLORS_LEN;
+use crate::DIRECTIONS;
+use crate::DIRECTIONS_LEN;
+use crate::KNIGHT_DIRECTIONS;
+use crate::ROOK_DIRECTIONS;
+use crate::SQUARES_LEN;
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static KNIGHT_ATTACKS: [Bitboard; SQUARES_LEN] = init_leaper(&KNIGHT_DIRECTIONS);
+static KING_ATTACKS: [Bitboard; SQUARES_LEN] = init_leaper(&DIRECTIONS);
+static PAWN_ATTACKS: [[Bitboard; SQUARES_LEN]; COLORS_LEN] = init_pawn();
+
+/// Returns the bit of the square with the given file and rank or 0 if it is off the board.
+const fn bit(file: i32, rank: i32) -> u64 {
+    if file >= 0 && file < 8 && rank >= 0 && rank < 8 { 1 << (rank * 8 + file) } else { 0 }
+}
+
+const fn init_leaper(directions: &[Direction; DIRECTIONS_LEN]) -> [Bitboard; SQUARES_LEN] {
+    let mut table = [Bitboard::EMPTY; SQUARES_LEN];
+    let mut sq = 0;
+    while sq < SQUARES_LEN {
+        let file = (sq & 7) as i32;
+        let rank = (sq >> 3) as i32;
+        let mut bb = 0;
+        let mut d = 0;
+        while d < DIRECTIONS_LEN {
+            bb |= bit(file + directions[d].file_delta(), rank + directions[d].rank_delta());
+            d += 1;
+        }
+        table[sq] = Bitboard(bb);
+        sq += 1;
+    }
+    table
+}
+
+const fn init_pawn() -> [[Bitboard; SQUARES_LEN]; COLORS_LEN] {
+    let mut table = [[Bitboard::EMPTY; SQUARES_LEN]; COLORS_LEN];
+    let mut c = 0;
+    while c < COLORS_LEN {
+        let forward = COLORS[c].direction();
+        let mut sq = 0;
+        while sq < SQUARES_LEN {
+            let file = (sq & 7) as i32;
+            let rank = (sq >> 3) as i32;
+            table[c][sq] = Bitboard(bit(file - 1, rank + forward) | bit(file + 1, rank + forward));
+            sq += 1;
+        }
+        c += 1;
+    }
+    table
+}
+
+/// Fancy magic entry of one square. The attacks for an occupancy are found at
+/// offset + ((occupied & mask) * magic) >> shift in the attack table.
+#[derive(Copy, Clone)]
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    #[inline]
+    fn index(&self, occupied: Bitboard) -> usize {
+        self.offset + ((occupied & self.mask).0.wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+// defines ROOK_MAGICS, ROOK_ATTACKS, BISHOP_MAGICS and BISHOP_ATTACKS
+// and with the pext feature ROOK_PEXT_ATTACKS and BISHOP_PEXT_ATTACKS
+include!(concat!(env!("OUT_DIR"), "/magics.rs"));
+
+/// Returns true if the PEXT backend is compiled in and supported by the CPU.
+#[inline]
+pub fn has_pext() -> bool {
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    {
+        std::arch::is_x86_feature_detected!("bmi2")
+    }
+    #[cfg(not(all(feature = "pext", target_arch = "x86_64")))]
+    {
+        false
+    }
+}
+
+/// The implementations of the slider attacks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Backend {
+    Magic,
+    Pext,
+}
+
+/// Set if rook() and bishop() have to use magics although PEXT is available
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+static FORCE_MAGIC: AtomicBool = AtomicBool::new(false);
+
+/// Returns the backend rook() and bishop() currently use.
+#[inline]
+pub fn backend() -> Backend {
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    if has_pext() && !FORCE_MAGIC.load(Ordering::Relaxed) {
+        return Backend::Pext;
+    }
+    Backend::Magic
+}
+
+/// Selects the backend of rook() and bishop() for the whole process and returns
+/// the backend in effect. PEXT is only used if has_pext() is true.
+pub fn set_backend(backend: Backend) -> Backend {
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    FORCE_MAGIC.store(backend == Backend::Magic, Ordering::Relaxed);
+    #[cfg(not(all(feature = "pext", target_arch = "x86_64")))]
+    let _ = backend;
+    self::backend()
+}
+
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+mod pext {
+    use super::*;
+    use std::arch::x86_64::_pext_u64;
+
+    /// # Safety
+    /// The CPU must support BMI2.
+    #[inline]
+    #[target_feature(enable = "bmi2")]
+    pub(super) unsafe fn rook(sq: Square, occupied: Bitboard) -> Bitboard {
+        let m = &ROOK_MAGICS[sq.to_index()];
+        ROOK_PEXT_ATTACKS[m.offset + _pext_u64(occupied.0, m.mask.0) as usize]
+    }
+
+    /// # Safety
+    /// The CPU must support BMI2.
+    #[inline]
+    #[target_feature(enable = "bmi2")]
+    pub(super) unsafe fn bishop(sq: Square, occupied: Bitboard) -> Bitboard {
+        let m = &BISHOP_MAGICS[sq.to_index()];
+        BISHOP_PEXT_ATTACKS[m.offset + _pext_u64(occupied.0, m.mask.0) as usize]
+    }
+}
+
+/// Returns the squares a rook on the given square attacks with the given occupied
+/// squares. The attacks include the first blocker in each direction.
+#[inline]
+pub fn rook(sq: Square, occupied: Bitboard) -> Bitboard {
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    if backend() == Backend::Pext {
+        // SAFETY: the PEXT backend is only selected if the CPU supports BMI2
+        return unsafe { pext::rook(sq, occupied) };
+    }
+    rook_magic(sq, occupied)
+}
+
+/// Returns the squares a bishop on the given square attacks with the given occupied
+/// squares. The attacks include the first blocker in each direction.
+#[inline]
+pub fn bishop(sq: Square, occupied: Bitboard) -> Bitboard {
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    if backend() == Backend::Pext {
+        // SAFETY: the PEXT backend is only selected if the CPU supports BMI2
+        return unsafe { pext::bishop(sq, occupied) };
+    }
+    bishop_magic(sq, occupied)
+}
+
+/// Rook attacks using the magic bitboard backend regardless of the pext feature.
+#[inline]
+pub fn rook_magic(sq: Square, occupied: Bitboard) -> Bitboard {
+    ROOK_ATTACKS[ROOK_MAGICS[sq.to_index()].index(occupied)]
+}
+
+/// Bishop attacks using the magic bitboard backend regardless of the pext feature.
+#[inline]
+pub fn bishop_magic(sq: Square, occupied: Bitboard) -> Bitboard {
+    BISHOP_ATTACKS[BISHOP_MAGICS[sq.to_index()].index(occupied)]
+}
+
+/// Returns the squares a queen on the given square attacks with the given occupied
+/// squares. The attacks include the first blocker in each direction.
+#[inline]
+pub fn queen(sq: Square, occupied: Bitboard) -> Bitboard { rook(sq, occupied) | bishop(sq, occupied) }
+
+/// Slow reference for rook() which walks the rays square by square.
+/// Only meant for testing and benchmarking.
+pub fn rook_slow(sq: Square, occupied: Bitboard) -> Bitboard { slow(sq, occupied, &ROOK_DIRECTIONS) }
+
+/// Slow reference for bishop() which walks the rays square by square.
+/// Only meant for testing and benchmarking.
+pub fn bishop_slow(sq: Square, occupied: Bitboard) -> Bitboard { slow(sq, occupied, &BISHOP_DIRECTIONS) }
+
+fn slow(sq: Square, occupied: Bitboard, directions: &[Direction; 4]) -> Bitboard {
+    let mut bb = Bitboard::EMPTY;
+    for d in directions {
+        let mut s = sq;
+        while let Some(next) = s.offset(*d) {
+            bb.set(next);
+            if occupied.has(next) {
+                break;
+            }
+            s = next;
+        }
+    }
+    bb
+}
+
+/// Returns the squares a knight on the given square attacks.
+#[inline]
+pub fn knight(sq: Square) -> Bitboard { KNIGHT_ATTACKS[sq.to_index()] }
+
+/// Returns the squares a king on the given square attacks.
+#[inline]
+pub fn king(sq: Square) -> Bitboard { KING_ATTACKS[sq.to_index()] }
+
+/// Returns the squares a pawn of the given color on the given square captures on.
+#[inline]
+pub fn pawn(c: Color, sq: Square) -> Bitboard { PAWN_ATTACKS[c.index()][sq.to_index()] }
+
+/// Returns the squares the given piece on the given square attacks with the given
+/// occupied squares. For pawns these are the capture squares only.
+#[inline]
+pub fn piece(p: Piece, sq: Square, occupied: Bitboard) -> Bitboard {
+    match p.type_of() {
+        PieceType::Pawn => pawn(p.color_of(), sq),
+        PieceType::Knight => knight(sq),
+        PieceType::Bishop => bishop(sq, occupied),
+        PieceType::Rook => rook(sq, occupied),
+        PieceType::Queen => queen(sq, occupied),
+        PieceType::King => king(sq),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SQUARES;
+
+    /// collects all squares reachable from sq by the given (file, rank) offsets
+    fn reference(sq: Square, offsets: &[(i32, i32)]) -> Bitboard {
+        let file = sq.file_of().to_index() as i32;
+        let rank = sq.rank_of().to_index() as i32;
+        let mut bb = Bitboard::EMPTY;
+        for (df, dr) in offsets {
+            let (f, r) = (file + df, rank + dr);
+            if (0..8).contains(&f) && (0..8).contains(&r) {
+                bb.set(SQUARES[(r * 8 + f) as usize]);
+            }
+        }
+        bb
+    }
+
+    #[test]
+    fn test_knight() {
+        let offsets = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+        for sq in SQUARES {
+            assert_eq!(reference(sq, &offsets), knight(sq), "{}", sq);
+        }
+        assert_eq!(2, knight(Square::A1).popcount());
+        assert_eq!(8, knight(Square::E4).popcount());
+        assert!(knight(Square::G1).has(Square::F3));
+    }
+
+    #[test]
+    fn test_king() {
+        let offsets = [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
+        for sq in SQUARES {
+            assert_eq!(reference(sq, &offsets), king(sq), "{}", sq);
+        }
+        assert_eq!(3, king(Square::H8).popcount());
+    }
+
+    #[test]
+    fn test_piece() {
+        let occupied = Bitboard::from(Square::E6);
+        assert_eq!(pawn(Color::Black, Square::E4), piece(Piece::BlackPawn, Square::E4, occupied));
+        assert_eq!(knight(Square::E4), piece(Piece::WhiteKnight, Square::E4, occupied));
+        assert_eq!(queen(Square::E4, occupied), piece(Piece::BlackQueen, Square::E4, occupied));
+        assert!(!piece(Piece::WhiteRook, Square::E4, occupied).has(Square::E7));
+    }
+
+    #[test]
+    fn test_table_sizes() {
+        let last = ROOK_MAGICS[SQUARES_LEN - 1];
+        assert_eq!(102_400, last.offset + (1 << last.mask.popcount()));
+        assert_eq!(102_400, ROOK_ATTACKS.len());
+        let last = BISHOP_MAGICS[SQUARES_LEN - 1];
+        assert_eq!(5_248, last.offset + (1 << last.mask.popcount()));
+        assert_eq!(5_248, BISHOP_ATTACKS.len());
+    }
+
+    #[test]
+    fn test_sliders_all_occupancies() {
+        for sq in SQUARES {
+            // enumerate all subsets of the masks (Carry-Rippler)
+            let mask = ROOK_MAGICS[sq.to_index()].mask.0;
+            let mut occupied: u64 = 0;
+            loop {
+                assert_eq!(rook_slow(sq, Bitboard(occupied)), rook(sq, Bitboard(occupied)), "{} {:#x}", sq, occupied);
+                occupied = occupied.wrapping_sub(mask) & mask;
+                if occupied == 0 {
+                    break;
+                }
+            }
+            let mask = BISHOP_MAGICS[sq.to_index()].mask.0;
+            loop {
+                assert_eq!(bishop_slow(sq, Bitboard(occupied)), bishop(sq, Bitboard(occupied)), "{} {:#x}", sq, occupied);
+                occupied = occupied.wrapping_sub(mask) & mask;
+                if occupied == 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sliders() {
+        // occupied squares outside of the mask (edges, the square itself) must not matter
+        let mut state: u64 = 0x1234_5678_9ABC_DEF1;
+        for _ in 0..1000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let occupied = Bitboard(state);
+            for sq in SQUARES {
+                assert_eq!(rook_slow(sq, occupied), rook(sq, occupied));
+                assert_eq!(bishop_slow(sq, occupied), bishop(sq, occupied));
+                assert_eq!(rook_slow(sq, occupied) | bishop_slow(sq, occupied), queen(sq, occupied));
+            }
+        }
+        assert_eq!(14, rook(Square::A1, Bitboard::EMPTY).popcount());
+        assert_eq!(7, bishop(Square::A1, Bitboard::EMPTY).popcount());
+        assert_eq!(27, queen(Square::E4, Bitboard::EMPTY).popcount());
+        let occupied = Bitboard::from(Square::E6) | Bitboard::from(Square::C4);
+        assert_eq!(Square::E4.ray(Direction::North) ^ Square::E6.ray(Direction::North)
+                       | Bitboard::from(Square::D4) | Bitboard::from(Square::C4)
+                       | Square::E4.ray(Direction::East)
+                       | Square::E4.ray(Direction::South),
+                   rook(Square::E4, occupied));
+    }
+
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    #[test]
+    fn test_pext_equals_magic() {
+        if !has_pext() {
+            eprintln!("test_pext_equals_magic skipped: the CPU does not support BMI2");
+            return;
+        }
+        for sq in SQUARES {
+            for mask in [ROOK_MAGICS[sq.to_index()].mask.0, BISHOP_MAGICS[sq.to_index()].mask.0] {
+                let mut occupied: u64 = 0;
+                loop {
+                    let occ = Bitboard(occupied);
+                    unsafe {
+                        assert_eq!(rook_magic(sq, occ), pext::rook(sq, occ));
+                        assert_eq!(bishop_magic(sq, occ), pext::bishop(sq, occ));
+                    }
+                    occupied = occupied.wrapping_sub(mask) & mask;
+                    if occupied == 0 {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_backend() {
+        assert_eq!(Backend::Magic, set_backend(Backend::Magic));
+        assert_eq!(if has_pext() { Backend::Pext } else { Backend::Magic }, set_backend(Backend::Pext));
+    }
+
+    #[test]
+    fn test_pawn() {
+        for c in COLORS {
+            let offsets = [(-1, c.direction()), (1, c.direction())];
+            for sq in SQUARES {
+                assert_eq!(reference(sq, &offsets), pawn(c, sq), "{} {}", c, sq);
+            }
+        }
+        assert_eq!(Bitboard::from(Square::D5) | Bitboard::from(Square::F5), pawn(Color::White, Square::E4));
+        assert_eq!(Bitboard::from(Square::D3) | Bitboard::from(Square::F3), pawn(Color::Black, Square::E4));
+        assert_eq!(Bitboard::from(Square::B3), pawn(Color::White, Square::A2));
+        assert_eq!(Bitboard::EMPTY, pawn(Color::White, Square::C8));
+    }
+
+}
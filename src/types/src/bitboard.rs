@@ -0,0 +1,368 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+use crate::File;
+use crate::Rank;
+use crate::Square;
+use crate::FILES;
+use crate::RANKS;
+
+/// A set of squares stored as a 64-bit integer where bit 0 is a1 and bit 63 is h8.
+/// The bit order follows the index order of SQUARES.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash, Default)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+
+    /// The empty set
+    pub const EMPTY: Bitboard = Bitboard(0);
+
+    /// The set of all 64 squares
+    pub const ALL: Bitboard = Bitboard(!0);
+
+    pub const FILE_A: Bitboard = Bitboard(0x0101_0101_0101_0101);
+    pub const FILE_B: Bitboard = Bitboard(Bitboard::FILE_A.0 << 1);
+    pub const FILE_C: Bitboard = Bitboard(Bitboard::FILE_A.0 << 2);
+    pub const FILE_D: Bitboard = Bitboard(Bitboard::FILE_A.0 << 3);
+    pub const FILE_E: Bitboard = Bitboard(Bitboard::FILE_A.0 << 4);
+    pub const FILE_F: Bitboard = Bitboard(Bitboard::FILE_A.0 << 5);
+    pub const FILE_G: Bitboard = Bitboard(Bitboard::FILE_A.0 << 6);
+    pub const FILE_H: Bitboard = Bitboard(Bitboard::FILE_A.0 << 7);
+
+    pub const RANK_1: Bitboard = Bitboard(0xFF);
+    pub const RANK_2: Bitboard = Bitboard(Bitboard::RANK_1.0 << 8);
+    pub const RANK_3: Bitboard = Bitboard(Bitboard::RANK_1.0 << (8 * 2));
+    pub const RANK_4: Bitboard = Bitboard(Bitboard::RANK_1.0 << (8 * 3));
+    pub const RANK_5: Bitboard = Bitboard(Bitboard::RANK_1.0 << (8 * 4));
+    pub const RANK_6: Bitboard = Bitboard(Bitboard::RANK_1.0 << (8 * 5));
+    pub const RANK_7: Bitboard = Bitboard(Bitboard::RANK_1.0 << (8 * 6));
+    pub const RANK_8: Bitboard = Bitboard(Bitboard::RANK_1.0 << (8 * 7));
+
+    /// Returns the number of squares in the set.
+    #[inline]
+    pub fn popcount(&self) -> u32 { self.0.count_ones() }
+
+    /// Returns true if no square is in the set.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.0 == 0 }
+
+    /// Returns true if the given square is in the set.
+    #[inline]
+    pub fn has(&self, sq: Square) -> bool { self.0 & (1 << sq.to_index()) != 0 }
+
+    /// Adds the given square to the set.
+    #[inline]
+    pub fn set(&mut self, sq: Square) { self.0 |= 1 << sq.to_index() }
+
+    /// Removes the given square from the set.
+    #[inline]
+    pub fn clear(&mut self, sq: Square) { self.0 &= !(1 << sq.to_index()) }
+
+    /// Returns the least significant square of the set.
+    /// The set must not be empty.
+    #[inline]
+    pub fn lsb(&self) -> Square {
+        debug_assert!(!self.is_empty(), "lsb of empty bitboard");
+        Square::wrapping_from_index(self.0.trailing_zeros() as usize)
+    }
+
+    /// Returns the most significant square of the set.
+    /// The set must not be empty.
+    #[inline]
+    pub fn msb(&self) -> Square {
+        debug_assert!(!self.is_empty(), "msb of empty bitboard");
+        Square::wrapping_from_index(63 - self.0.leading_zeros() as usize)
+    }
+
+    /// Removes the least significant square from the set and returns it.
+    /// The set must not be empty.
+    #[inline]
+    pub fn pop_lsb(&mut self) -> Square {
+        let sq = self.lsb();
+        self.0 &= self.0 - 1;
+        sq
+    }
+
+}
+
+/// Bitboards for all files indexed by File::to_index()
+pub const FILES_BB: [Bitboard; 8] = [
+    Bitboard::FILE_A,
+    Bitboard::FILE_B,
+    Bitboard::FILE_C,
+    Bitboard::FILE_D,
+    Bitboard::FILE_E,
+    Bitboard::FILE_F,
+    Bitboard::FILE_G,
+    Bitboard::FILE_H
+];
+
+/// Bitboards for all ranks indexed by Rank::to_index()
+pub const RANKS_BB: [Bitboard; 8] = [
+    Bitboard::RANK_1,
+    Bitboard::RANK_2,
+    Bitboard::RANK_3,
+    Bitboard::RANK_4,
+    Bitboard::RANK_5,
+    Bitboard::RANK_6,
+    Bitboard::RANK_7,
+    Bitboard::RANK_8
+];
+
+impl File {
+
+    /// Returns the Bitboard of all squares on this file.
+    #[inline]
+    pub fn bitboard(&self) -> Bitboard { FILES_BB[self.to_index()] }
+
+}
+
+impl Rank {
+
+    /// Returns the Bitboard of all squares on this rank.
+    #[inline]
+    pub fn bitboard(&self) -> Bitboard { RANKS_BB[self.to_index()] }
+
+}
+
+impl Square {
+
+    /// Returns the Bitboard containing only this square.
+    #[inline]
+    pub fn bitboard(&self) -> Bitboard { Bitboard(1 << self.to_index()) }
+
+}
+
+impl From<Square> for Bitboard {
+    #[inline]
+    fn from(sq: Square) -> Self { sq.bitboard() }
+}
+
+impl From<File> for Bitboard {
+    #[inline]
+    fn from(f: File) -> Self { f.bitboard() }
+}
+
+impl From<Rank> for Bitboard {
+    #[inline]
+    fn from(r: Rank) -> Self { r.bitboard() }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+    #[inline]
+    fn bitor(self, rhs: Bitboard) -> Bitboard { Bitboard(self.0 | rhs.0) }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+    #[inline]
+    fn bitand(self, rhs: Bitboard) -> Bitboard { Bitboard(self.0 & rhs.0) }
+}
+
+impl BitXor for Bitboard {
+    type Output = Bitboard;
+    #[inline]
+    fn bitxor(self, rhs: Bitboard) -> Bitboard { Bitboard(self.0 ^ rhs.0) }
+}
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+    #[inline]
+    fn not(self) -> Bitboard { Bitboard(!self.0) }
+}
+
+impl BitOrAssign for Bitboard {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Bitboard) { self.0 |= rhs.0 }
+}
+
+impl BitAndAssign for Bitboard {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: Bitboard) { self.0 &= rhs.0 }
+}
+
+impl BitXorAssign for Bitboard {
+    #[inline]
+    fn bitxor_assign(&mut self, rhs: Bitboard) { self.0 ^= rhs.0 }
+}
+
+/// Iterates over the squares of the set from a1 to h8.
+/// Iterating from the back returns the squares from h8 to a1.
+impl Iterator for Bitboard {
+    type Item = Square;
+
+    #[inline]
+    fn next(&mut self) -> Option<Square> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.pop_lsb())
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.popcount() as usize;
+        (n, Some(n))
+    }
+}
+
+impl DoubleEndedIterator for Bitboard {
+    #[inline]
+    fn next_back(&mut self) -> Option<Square> {
+        if self.is_empty() {
+            None
+        } else {
+            let sq = self.msb();
+            self.clear(sq);
+            Some(sq)
+        }
+    }
+}
+
+/// Prints the set as an 8x8 board with rank 8 at the top and file a on the left.
+impl fmt::Display for Bitboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for r in RANKS.iter().rev() {
+            write!(f, "{} ", r)?;
+            for fl in FILES.iter() {
+                let sq = Square::from_file_rank(*fl, *r);
+                write!(f, "{}", if self.has(sq) { " X" } else { " ." })?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "  ")?;
+        for fl in FILES.iter() {
+            write!(f, " {}", fl)?;
+        }
+        writeln!(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SQUARES;
+
+    #[test]
+    fn test_from_square() {
+        assert_eq!(Bitboard(1), Bitboard::from(Square::A1));
+        assert_eq!(Bitboard(1 << 63), Bitboard::from(Square::H8));
+        for sq in SQUARES {
+            let bb = Bitboard::from(sq);
+            assert_eq!(1, bb.popcount());
+            assert!(bb.has(sq));
+            assert_eq!(sq, bb.lsb());
+        }
+    }
+
+    #[test]
+    fn test_file_rank_masks() {
+        for sq in SQUARES {
+            assert!(sq.file_of().bitboard().has(sq));
+            assert!(sq.rank_of().bitboard().has(sq));
+        }
+        for i in 0..8 {
+            assert_eq!(8, FILES_BB[i].popcount());
+            assert_eq!(8, RANKS_BB[i].popcount());
+        }
+        assert_eq!(Bitboard::ALL, FILES_BB.iter().fold(Bitboard::EMPTY, |a, b| a | *b));
+        assert_eq!(Bitboard::ALL, RANKS_BB.iter().fold(Bitboard::EMPTY, |a, b| a | *b));
+        assert_eq!(Bitboard::from(Square::E4), Bitboard::from(File::E) & Bitboard::from(Rank::Four));
+    }
+
+    #[test]
+    fn test_operators() {
+        let a = Bitboard::from(Square::A1) | Bitboard::from(Square::E4);
+        let b = Bitboard::from(Square::E4) | Bitboard::from(Square::H8);
+        assert_eq!(Bitboard::from(Square::E4), a & b);
+        assert_eq!(Bitboard::from(Square::A1) | Bitboard::from(Square::H8), a ^ b);
+        assert_eq!(62, (!a).popcount());
+        let mut c = a;
+        c |= b;
+        assert_eq!(3, c.popcount());
+        c &= b;
+        assert_eq!(b, c);
+        c ^= b;
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    fn test_set_clear() {
+        let mut bb = Bitboard::EMPTY;
+        bb.set(Square::C3);
+        bb.set(Square::F6);
+        assert!(bb.has(Square::C3));
+        assert!(bb.has(Square::F6));
+        bb.clear(Square::C3);
+        assert!(!bb.has(Square::C3));
+        assert_eq!(1, bb.popcount());
+    }
+
+    #[test]
+    fn test_lsb_msb() {
+        let mut bb = Bitboard::from(Square::B2) | Bitboard::from(Square::G7) | Bitboard::from(Square::D4);
+        assert_eq!(Square::B2, bb.lsb());
+        assert_eq!(Square::G7, bb.msb());
+        assert_eq!(Square::B2, bb.pop_lsb());
+        assert_eq!(Square::D4, bb.pop_lsb());
+        assert_eq!(Square::G7, bb.pop_lsb());
+        assert!(bb.is_empty());
+    }
+
+    #[test]
+    fn test_iterator() {
+        let bb = Bitboard::RANK_2;
+        assert_eq!(8, bb.count());
+        let squares: Vec<Square> = bb.collect();
+        assert_eq!(&SQUARES[8..16], &squares[..]);
+        assert_eq!(64, Bitboard::ALL.count());
+        assert_eq!(0, Bitboard::EMPTY.count());
+        let squares: Vec<Square> = Bitboard::FILE_A.rev().collect();
+        assert_eq!(vec![Square::A8, Square::A7, Square::A6, Square::A5, Square::A4, Square::A3, Square::A2, Square::A1], squares);
+        let mut bb = Bitboard::from(Square::C3) | Bitboard::from(Square::D4) | Bitboard::from(Square::E5);
+        assert_eq!(Some(Square::C3), bb.next());
+        assert_eq!(Some(Square::E5), bb.next_back());
+        assert_eq!(Some(Square::D4), bb.next_back());
+        assert_eq!(None, bb.next());
+    }
+
+    #[test]
+    fn test_print() {
+        let bb = Bitboard::from(Square::A1) | Bitboard::from(Square::H8);
+        let expected = "\
+8  . . . . . . . X
+7  . . . . . . . .
+6  . . . . . . . .
+5  . . . . . . . .
+4  . . . . . . . .
+3  . . . . . . . .
+2  . . . . . . . .
+1  X . . . . . . .
+   a b c d e f g h
+";
+        assert_eq!(expected, format!("{}", bb));
+    }
+
+}
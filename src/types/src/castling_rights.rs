@@ -0,0 +1,230 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+use std::str::FromStr;
+use crate::Color;
+use crate::Error;
+use crate::Square;
+
+/// Castling rights of both sides as a set of four flags.
+/// Only the four low bits can be set, so `index` is always below CASTLING_RIGHTS_LEN.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash, Default)]
+pub struct CastlingRights(u8);
+
+/// Number of different castling right combinations
+pub const CASTLING_RIGHTS_LEN: usize = 16;
+
+impl CastlingRights {
+
+    pub const NONE: CastlingRights = CastlingRights(0);
+    pub const WHITE_OO: CastlingRights = CastlingRights(1);
+    pub const WHITE_OOO: CastlingRights = CastlingRights(2);
+    pub const BLACK_OO: CastlingRights = CastlingRights(4);
+    pub const BLACK_OOO: CastlingRights = CastlingRights(8);
+    pub const WHITE: CastlingRights = CastlingRights(1 | 2);
+    pub const BLACK: CastlingRights = CastlingRights(4 | 8);
+    pub const ANY: CastlingRights = CastlingRights(15);
+
+    /// Returns the rights for the given bits or None if bits above the four flags are set.
+    #[inline]
+    pub const fn try_from_bits(bits: u8) -> Option<CastlingRights> {
+        if (bits as usize) < CASTLING_RIGHTS_LEN { Some(CastlingRights(bits)) } else { None }
+    }
+
+    /// Returns the flags as bits, WHITE_OO being the lowest.
+    #[inline]
+    pub const fn bits(&self) -> u8 { self.0 }
+
+    /// Returns the index (0-15) of this combination of rights e.g. for Zobrist lookups.
+    #[inline]
+    pub fn index(&self) -> usize { self.0 as usize }
+
+    /// Returns true if all of the given rights are set.
+    #[inline]
+    pub fn has(&self, rights: CastlingRights) -> bool { self.0 & rights.0 == rights.0 }
+
+    /// Returns true if no right is set.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.0 == 0 }
+
+    /// Returns these rights without the given rights.
+    #[inline]
+    pub fn remove(&self, rights: CastlingRights) -> CastlingRights { CastlingRights(self.0 & !rights.0) }
+
+    /// Returns the king side castling right of the given color.
+    #[inline]
+    pub fn king_side(c: Color) -> CastlingRights {
+        match c {
+            Color::White => CastlingRights::WHITE_OO,
+            Color::Black => CastlingRights::BLACK_OO
+        }
+    }
+
+    /// Returns the queen side castling right of the given color.
+    #[inline]
+    pub fn queen_side(c: Color) -> CastlingRights {
+        match c {
+            Color::White => CastlingRights::WHITE_OOO,
+            Color::Black => CastlingRights::BLACK_OOO
+        }
+    }
+
+    /// Returns the rights which are lost when a piece moves from or to the given square.
+    /// A king leaving its start square loses both rights of its side, a rook leaving
+    /// or being captured on its start square loses the corresponding right.
+    #[inline]
+    pub const fn lost_by(sq: Square) -> CastlingRights {
+        match sq {
+            Square::E1 => CastlingRights::WHITE,
+            Square::H1 => CastlingRights::WHITE_OO,
+            Square::A1 => CastlingRights::WHITE_OOO,
+            Square::E8 => CastlingRights::BLACK,
+            Square::H8 => CastlingRights::BLACK_OO,
+            Square::A8 => CastlingRights::BLACK_OOO,
+            _ => CastlingRights::NONE
+        }
+    }
+
+}
+
+impl BitOr for CastlingRights {
+    type Output = CastlingRights;
+    #[inline]
+    fn bitor(self, rhs: CastlingRights) -> CastlingRights { CastlingRights(self.0 | rhs.0) }
+}
+
+impl BitOrAssign for CastlingRights {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: CastlingRights) { self.0 |= rhs.0 }
+}
+
+impl TryFrom<u8> for CastlingRights {
+    type Error = Error;
+    fn try_from(bits: u8) -> Result<Self, Self::Error> {
+        CastlingRights::try_from_bits(bits).ok_or_else(|| Error::InvalidCastlingRights { input: bits.to_string(), offset: 0 })
+    }
+}
+
+/// Parses the castling field of a FEN ("KQkq", "Kq", "-", ...).
+/// Each letter may only occur once.
+impl FromStr for CastlingRights {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "-" {
+            return Ok(CastlingRights::NONE);
+        }
+        let invalid = |offset| Error::InvalidCastlingRights { input: s.to_string(), offset };
+        if s.is_empty() {
+            return Err(invalid(0));
+        }
+        let mut rights = CastlingRights::NONE;
+        for (i, c) in s.chars().enumerate() {
+            let right = match c {
+                'K' => CastlingRights::WHITE_OO,
+                'Q' => CastlingRights::WHITE_OOO,
+                'k' => CastlingRights::BLACK_OO,
+                'q' => CastlingRights::BLACK_OOO,
+                _ => return Err(invalid(i)),
+            };
+            if rights.has(right) {
+                return Err(invalid(i));
+            }
+            rights |= right;
+        }
+        Ok(rights)
+    }
+}
+
+impl fmt::Display for CastlingRights {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "-");
+        }
+        if self.has(CastlingRights::WHITE_OO) { write!(f, "K")?; }
+        if self.has(CastlingRights::WHITE_OOO) { write!(f, "Q")?; }
+        if self.has(CastlingRights::BLACK_OO) { write!(f, "k")?; }
+        if self.has(CastlingRights::BLACK_OOO) { write!(f, "q")?; }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_remove() {
+        let cr = CastlingRights::ANY;
+        assert!(cr.has(CastlingRights::WHITE_OO));
+        assert!(cr.has(CastlingRights::BLACK));
+        let cr = cr.remove(CastlingRights::WHITE);
+        assert!(!cr.has(CastlingRights::WHITE_OO));
+        assert!(!cr.has(CastlingRights::WHITE_OOO));
+        assert!(cr.has(CastlingRights::BLACK));
+        assert_eq!(CastlingRights::BLACK, cr);
+        assert_eq!(12, cr.index());
+    }
+
+    #[test]
+    fn test_lost_by() {
+        let cr = CastlingRights::ANY;
+        assert_eq!(CastlingRights::BLACK, cr.remove(CastlingRights::lost_by(Square::E1)));
+        assert_eq!(CastlingRights::ANY.remove(CastlingRights::WHITE_OOO), cr.remove(CastlingRights::lost_by(Square::A1)));
+        assert_eq!(CastlingRights::ANY.remove(CastlingRights::BLACK_OO), cr.remove(CastlingRights::lost_by(Square::H8)));
+        assert_eq!(cr, cr.remove(CastlingRights::lost_by(Square::E4)));
+        assert_eq!(CastlingRights::king_side(Color::Black), CastlingRights::lost_by(Square::H8));
+        assert_eq!(CastlingRights::queen_side(Color::White), CastlingRights::lost_by(Square::A1));
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Ok(CastlingRights::ANY), CastlingRights::from_str("KQkq"));
+        assert_eq!(Ok(CastlingRights::NONE), CastlingRights::from_str("-"));
+        assert_eq!(Ok(CastlingRights::WHITE_OO | CastlingRights::BLACK_OOO), CastlingRights::from_str("Kq"));
+        assert_eq!(Err(Error::InvalidCastlingRights { input: "".to_string(), offset: 0 }), CastlingRights::from_str(""));
+        assert_eq!(Err(Error::InvalidCastlingRights { input: "KK".to_string(), offset: 1 }), CastlingRights::from_str("KK"));
+        assert_eq!(Err(Error::InvalidCastlingRights { input: "KQx".to_string(), offset: 2 }), CastlingRights::from_str("KQx"));
+        assert_eq!(Err(Error::InvalidCastlingRights { input: "-K".to_string(), offset: 0 }), CastlingRights::from_str("-K"));
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(Ok(CastlingRights::ANY), CastlingRights::try_from(15));
+        assert_eq!(Ok(CastlingRights::BLACK), CastlingRights::try_from(12));
+        assert_eq!(12, CastlingRights::BLACK.bits());
+        assert_eq!(Err(Error::InvalidCastlingRights { input: "16".to_string(), offset: 0 }), CastlingRights::try_from(16));
+        assert_eq!(None, CastlingRights::try_from_bits(255));
+    }
+
+    #[test]
+    fn test_print() {
+        assert_eq!("KQkq", format!("{}", CastlingRights::ANY));
+        assert_eq!("-", format!("{}", CastlingRights::NONE));
+        assert_eq!("Qk", format!("{}", CastlingRights::WHITE_OOO | CastlingRights::BLACK_OO));
+        for i in 0..CASTLING_RIGHTS_LEN {
+            let cr = CastlingRights::try_from(i as u8).unwrap();
+            assert_eq!(Ok(cr), CastlingRights::from_str(&cr.to_string()));
+        }
+    }
+
+}
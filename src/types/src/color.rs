@@ -20,6 +20,7 @@
  */
 
 use crate::error::Error;
+use crate::Rank;
 use std::fmt;
 use std::str::FromStr;
 
@@ -36,7 +37,14 @@ pub const COLORS: [Color; COLORS_LEN] = [Color::White,Color::Black];
 impl Color {
 
     #[inline]
-    pub fn index(&self) -> usize { *self as usize }
+    pub const fn index(&self) -> usize { *self as usize }
+
+    /// Returns the color for the given index (0=White, 1=Black) or None if
+    /// the index is out of range.
+    #[inline]
+    pub const fn try_from_index(i: usize) -> Option<Color> {
+        if i < COLORS_LEN { Some(COLORS[i]) } else { None }
+    }
 
     #[inline]
     pub fn flip(&self) -> Color {
@@ -47,25 +55,60 @@ impl Color {
     }
 
     #[inline]
-    pub fn direction(&self) -> i32 {
+    pub const fn direction(&self) -> i32 {
         match *self {
             Color::White => 1,
             Color::Black => -1
         }
     }
 
+    /// Returns the rank the pieces of this color start on.
+    #[inline]
+    pub fn back_rank(&self) -> Rank { Rank::One.relative_to(*self) }
+
+    /// Returns the rank the pawns of this color start on.
+    #[inline]
+    pub fn pawn_rank(&self) -> Rank { Rank::Two.relative_to(*self) }
+
+    /// Returns the rank the pawns of this color promote on.
+    #[inline]
+    pub fn promotion_rank(&self) -> Rank { Rank::Eight.relative_to(*self) }
+
 }
 
 impl FromStr for Color {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() < 1 {
-            return Err(Error::InvalidColor);
-        }
         match s {
             "w" => Ok(Color::White),
             "b" => Ok(Color::Black),
-            _ => { return Err(Error::InvalidColor); }
+            _ => Err(Error::InvalidColor {
+                input: s.to_string(),
+                offset: if s.starts_with('w') || s.starts_with('b') { 1 } else { 0 },
+            })
+        }
+    }
+}
+
+impl TryFrom<usize> for Color {
+    type Error = Error;
+    fn try_from(i: usize) -> Result<Self, Self::Error> {
+        Color::try_from_index(i).ok_or_else(|| Error::InvalidColor { input: i.to_string(), offset: 0 })
+    }
+}
+
+impl TryFrom<u8> for Color {
+    type Error = Error;
+    fn try_from(i: u8) -> Result<Self, Self::Error> { Color::try_from(i as usize) }
+}
+
+impl TryFrom<char> for Color {
+    type Error = Error;
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'w' => Ok(Color::White),
+            'b' => Ok(Color::Black),
+            _ => Err(Error::InvalidColor { input: c.to_string(), offset: 0 })
         }
     }
 }
@@ -93,12 +136,33 @@ mod tests {
         assert_eq!(1, Color::Black.index());
     }
 
+    #[test]
+    fn test_try_from() {
+        assert_eq!(Some(Color::Black), Color::try_from_index(1));
+        assert_eq!(None, Color::try_from_index(2));
+        assert_eq!(Ok(Color::White), Color::try_from(0usize));
+        assert_eq!(Ok(Color::Black), Color::try_from(1u8));
+        assert_eq!(Err(Error::InvalidColor { input: "2".to_string(), offset: 0 }), Color::try_from(2u8));
+        assert_eq!(Ok(Color::Black), Color::try_from('b'));
+        assert_eq!(Err(Error::InvalidColor { input: "x".to_string(), offset: 0 }), Color::try_from('x'));
+    }
+
     #[test]
     fn test_flip() {
         assert_eq!(Color::White, Color::Black.flip());
         assert_eq!(Color::Black, Color::White.flip());
     }
 
+    #[test]
+    fn test_ranks() {
+        assert_eq!(Rank::One, Color::White.back_rank());
+        assert_eq!(Rank::Eight, Color::Black.back_rank());
+        assert_eq!(Rank::Two, Color::White.pawn_rank());
+        assert_eq!(Rank::Seven, Color::Black.pawn_rank());
+        assert_eq!(Rank::Eight, Color::White.promotion_rank());
+        assert_eq!(Rank::One, Color::Black.promotion_rank());
+    }
+
     #[test]
     fn test_from_str() {
         let result = Color::from_str("w");
@@ -116,7 +180,13 @@ mod tests {
         let result = Color::from_str("a");
         match result {
             Ok(_) => panic!("Test failed"),
-            Err(e) => assert_eq!(Error::InvalidColor, e),
+            Err(e) => assert_eq!(Error::InvalidColor { input: "a".to_string(), offset: 0 }, e),
+        }
+
+        let result = Color::from_str("wb");
+        match result {
+            Ok(_) => panic!("Test failed"),
+            Err(e) => assert_eq!(Error::InvalidColor { input: "wb".to_string(), offset: 1 }, e),
         }
     }
 
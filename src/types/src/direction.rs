@@ -0,0 +1,264 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::Bitboard;
+use crate::Color;
+use crate::Square;
+
+/// Directions on the board seen from White's side (North is towards rank 8).
+/// Besides the eight king directions this also contains the eight knight jumps.
+#[derive(PartialOrd, PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+    NorthNorthEast,
+    EastNorthEast,
+    EastSouthEast,
+    SouthSouthEast,
+    SouthSouthWest,
+    WestSouthWest,
+    WestNorthWest,
+    NorthNorthWest
+}
+
+pub const DIRECTIONS_LEN: usize = 8;
+
+/// The eight directions a king (or a queen) can move to
+pub const DIRECTIONS: [Direction; DIRECTIONS_LEN] = [
+    Direction::North,
+    Direction::NorthEast,
+    Direction::East,
+    Direction::SouthEast,
+    Direction::South,
+    Direction::SouthWest,
+    Direction::West,
+    Direction::NorthWest
+];
+
+/// The orthogonal directions a rook moves along
+pub const ROOK_DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West
+];
+
+/// The diagonal directions a bishop moves along
+pub const BISHOP_DIRECTIONS: [Direction; 4] = [
+    Direction::NorthEast,
+    Direction::SouthEast,
+    Direction::SouthWest,
+    Direction::NorthWest
+];
+
+/// The eight jumps of a knight
+pub const KNIGHT_DIRECTIONS: [Direction; DIRECTIONS_LEN] = [
+    Direction::NorthNorthEast,
+    Direction::EastNorthEast,
+    Direction::EastSouthEast,
+    Direction::SouthSouthEast,
+    Direction::SouthSouthWest,
+    Direction::WestSouthWest,
+    Direction::WestNorthWest,
+    Direction::NorthNorthWest
+];
+
+impl Direction {
+
+    /// Returns the change of the file index when moving in this direction.
+    #[inline]
+    pub const fn file_delta(&self) -> i32 {
+        match *self {
+            Direction::North | Direction::South => 0,
+            Direction::NorthEast | Direction::East | Direction::SouthEast => 1,
+            Direction::SouthWest | Direction::West | Direction::NorthWest => -1,
+            Direction::NorthNorthEast | Direction::SouthSouthEast => 1,
+            Direction::EastNorthEast | Direction::EastSouthEast => 2,
+            Direction::SouthSouthWest | Direction::NorthNorthWest => -1,
+            Direction::WestSouthWest | Direction::WestNorthWest => -2,
+        }
+    }
+
+    /// Returns the change of the rank index when moving in this direction.
+    #[inline]
+    pub const fn rank_delta(&self) -> i32 {
+        match *self {
+            Direction::East | Direction::West => 0,
+            Direction::NorthWest | Direction::North | Direction::NorthEast => 1,
+            Direction::SouthEast | Direction::South | Direction::SouthWest => -1,
+            Direction::EastNorthEast | Direction::WestNorthWest => 1,
+            Direction::NorthNorthEast | Direction::NorthNorthWest => 2,
+            Direction::EastSouthEast | Direction::WestSouthWest => -1,
+            Direction::SouthSouthEast | Direction::SouthSouthWest => -2,
+        }
+    }
+
+    /// Returns the change of the square index when moving in this direction.
+    /// This does not take wrapping over the board edges into account.
+    #[inline]
+    pub const fn offset(&self) -> i32 { self.rank_delta() * 8 + self.file_delta() }
+
+    /// Returns the opposite direction.
+    #[inline]
+    pub const fn opposite(&self) -> Direction {
+        match *self {
+            Direction::North => Direction::South,
+            Direction::NorthEast => Direction::SouthWest,
+            Direction::East => Direction::West,
+            Direction::SouthEast => Direction::NorthWest,
+            Direction::South => Direction::North,
+            Direction::SouthWest => Direction::NorthEast,
+            Direction::West => Direction::East,
+            Direction::NorthWest => Direction::SouthEast,
+            Direction::NorthNorthEast => Direction::SouthSouthWest,
+            Direction::EastNorthEast => Direction::WestSouthWest,
+            Direction::EastSouthEast => Direction::WestNorthWest,
+            Direction::SouthSouthEast => Direction::NorthNorthWest,
+            Direction::SouthSouthWest => Direction::NorthNorthEast,
+            Direction::WestSouthWest => Direction::EastNorthEast,
+            Direction::WestNorthWest => Direction::EastSouthEast,
+            Direction::NorthNorthWest => Direction::SouthSouthEast,
+        }
+    }
+
+    /// Returns the squares which may be shifted in this direction without
+    /// wrapping around the a- or h-file.
+    #[inline]
+    fn shift_mask(&self) -> Bitboard {
+        match self.file_delta() {
+            2 => !(Bitboard::FILE_G | Bitboard::FILE_H),
+            1 => !Bitboard::FILE_H,
+            -1 => !Bitboard::FILE_A,
+            -2 => !(Bitboard::FILE_A | Bitboard::FILE_B),
+            _ => Bitboard::ALL,
+        }
+    }
+
+}
+
+impl Square {
+
+    /// Returns the square reached by moving one step into the given direction
+    /// or None if this would leave the board.
+    #[inline]
+    pub fn offset(&self, dir: Direction) -> Option<Square> {
+        let file = self.file_of().to_index() as i32 + dir.file_delta();
+        let rank = self.rank_of().to_index() as i32 + dir.rank_delta();
+        if (0..8).contains(&file) && (0..8).contains(&rank) {
+            Some(Square::wrapping_from_index((rank * 8 + file) as usize))
+        } else {
+            None
+        }
+    }
+
+}
+
+impl Bitboard {
+
+    /// Moves all squares of the set one step into the given direction.
+    /// Squares which would leave the board are dropped instead of wrapping
+    /// around to the other side.
+    #[inline]
+    pub fn shift(&self, dir: Direction) -> Bitboard {
+        let masked = (*self & dir.shift_mask()).0;
+        let offset = dir.offset();
+        if offset > 0 {
+            Bitboard(masked << offset)
+        } else {
+            Bitboard(masked >> -offset)
+        }
+    }
+
+}
+
+impl Color {
+
+    /// Returns the direction pawns of this color move to.
+    #[inline]
+    pub fn forward(&self) -> Direction {
+        match *self {
+            Color::White => Direction::North,
+            Color::Black => Direction::South
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SQUARES;
+
+    #[test]
+    fn test_offset() {
+        assert_eq!(8, Direction::North.offset());
+        assert_eq!(-9, Direction::SouthWest.offset());
+        assert_eq!(17, Direction::NorthNorthEast.offset());
+        assert_eq!(-10, Direction::WestSouthWest.offset());
+        for d in DIRECTIONS.iter().chain(KNIGHT_DIRECTIONS.iter()) {
+            assert_eq!(-d.offset(), d.opposite().offset());
+        }
+    }
+
+    #[test]
+    fn test_square_offset() {
+        assert_eq!(Some(Square::E5), Square::E4.offset(Direction::North));
+        assert_eq!(Some(Square::B1), Square::A1.offset(Direction::East));
+        assert_eq!(None, Square::A1.offset(Direction::West));
+        assert_eq!(None, Square::H4.offset(Direction::East));
+        assert_eq!(None, Square::H4.offset(Direction::NorthEast));
+        assert_eq!(None, Square::E8.offset(Direction::North));
+        assert_eq!(None, Square::E1.offset(Direction::South));
+        assert_eq!(Some(Square::F3), Square::G1.offset(Direction::NorthNorthWest));
+        assert_eq!(None, Square::G1.offset(Direction::EastNorthEast));
+        assert_eq!(None, Square::B1.offset(Direction::WestNorthWest));
+    }
+
+    #[test]
+    fn test_shift() {
+        for d in DIRECTIONS.iter().chain(KNIGHT_DIRECTIONS.iter()) {
+            for sq in SQUARES {
+                let expected = match sq.offset(*d) {
+                    Some(to) => Bitboard::from(to),
+                    None => Bitboard::EMPTY,
+                };
+                assert_eq!(expected, Bitboard::from(sq).shift(*d), "{} {:?}", sq, d);
+            }
+        }
+        assert_eq!(Bitboard::RANK_3, Bitboard::RANK_2.shift(Direction::North));
+        assert_eq!(Bitboard::EMPTY, Bitboard::FILE_H.shift(Direction::East));
+    }
+
+    #[test]
+    fn test_forward() {
+        assert_eq!(Direction::North, Color::White.forward());
+        assert_eq!(Direction::South, Color::Black.forward());
+        assert_eq!(Color::White.direction() * 8, Color::White.forward().offset());
+        assert_eq!(Color::Black.direction() * 8, Color::Black.forward().offset());
+    }
+
+}
@@ -19,25 +19,171 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
-use failure::Fail;
+use std::fmt;
+
+/// The fields of a FEN string in the order they appear.
+#[derive(PartialOrd, PartialEq, Eq, Copy, Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FenField {
+    Placement,
+    SideToMove,
+    CastlingRights,
+    EnPassant,
+    HalfmoveClock,
+    FullmoveNumber
+}
+
+impl fmt::Display for FenField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                FenField::Placement => "piece placement",
+                FenField::SideToMove => "side to move",
+                FenField::CastlingRights => "castling rights",
+                FenField::EnPassant => "en passant square",
+                FenField::HalfmoveClock => "halfmove clock",
+                FenField::FullmoveNumber => "fullmove number"
+            }
+        )
+    }
+}
 
 /// Sometimes, bad stuff happens.
-#[derive(Clone, Debug, Fail, PartialEq)]
+/// Every variant carries the offending input and the character offset into it
+/// at which parsing failed so front-ends can report precisely what was wrong.
+/// Offsets count chars, not bytes, so they also point at the right place in
+/// non-ASCII input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Error {
 
     /// An attempt was made to create a color from an invalid string
-    #[fail(display = "The string specified does not contain a valid notation notation for a color")]
-    InvalidColor,
+    InvalidColor { input: String, offset: usize },
 
     /// An attempt was made to convert a string not equal to "a"-"h" to a file
-    #[fail(display = "The string specified does not contain a valid file")]
-    InvalidFile,
+    InvalidFile { input: String, offset: usize },
 
     /// An attempt was made to convert a string not equal to "1"-"8" to a rank
-    #[fail(display = "The string specified does not contain a valid rank")]
-    InvalidRank,
+    InvalidRank { input: String, offset: usize },
+
+    /// An attempt was made to convert a string which is not an algebraic notation square
+    InvalidSquare { input: String, offset: usize },
+
+    /// An attempt was made to convert a string not equal to one of "PNBRQK" (any case) to a piece type
+    InvalidPieceType { input: String, offset: usize },
+
+    /// An attempt was made to convert a string not equal to a FEN piece letter to a piece
+    InvalidPiece { input: String, offset: usize },
+
+    /// An attempt was made to parse a FEN castling field other than "-" or a subset of "KQkq"
+    InvalidCastlingRights { input: String, offset: usize },
+
+    /// An attempt was made to parse a string which is not a move in UCI long algebraic notation
+    InvalidMove { input: String, offset: usize },
+
+    /// A syntactically valid move is not legal in the position it was applied to
+    IllegalMove { input: String, offset: usize },
+
+    /// A FEN string could not be parsed. The offset is relative to the whole FEN.
+    InvalidFen { field: FenField, input: String, offset: usize },
+
+    /// A PGN text could not be parsed. The offset is relative to the given line.
+    InvalidPgn { line: usize, input: String, offset: usize },
+
+}
+
+impl Error {
+
+    /// Returns the input which could not be parsed.
+    pub fn input(&self) -> &str {
+        match self {
+            Error::InvalidColor { input, .. }
+            | Error::InvalidFile { input, .. }
+            | Error::InvalidRank { input, .. }
+            | Error::InvalidSquare { input, .. }
+            | Error::InvalidPieceType { input, .. }
+            | Error::InvalidPiece { input, .. }
+            | Error::InvalidCastlingRights { input, .. }
+            | Error::InvalidMove { input, .. }
+            | Error::IllegalMove { input, .. }
+            | Error::InvalidFen { input, .. }
+            | Error::InvalidPgn { input, .. } => input
+        }
+    }
+
+    /// Returns the character offset into the input at which parsing failed.
+    pub fn offset(&self) -> usize {
+        match self {
+            Error::InvalidColor { offset, .. }
+            | Error::InvalidFile { offset, .. }
+            | Error::InvalidRank { offset, .. }
+            | Error::InvalidSquare { offset, .. }
+            | Error::InvalidPieceType { offset, .. }
+            | Error::InvalidPiece { offset, .. }
+            | Error::InvalidCastlingRights { offset, .. }
+            | Error::InvalidMove { offset, .. }
+            | Error::IllegalMove { offset, .. }
+            | Error::InvalidFen { offset, .. }
+            | Error::InvalidPgn { offset, .. } => *offset
+        }
+    }
+
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidColor { input, offset } =>
+                write!(f, "invalid color \"{}\" at character {}: expected \"w\" or \"b\"", input, offset),
+            Error::InvalidFile { input, offset } =>
+                write!(f, "invalid file \"{}\" at character {}: expected \"a\"-\"h\"", input, offset),
+            Error::InvalidRank { input, offset } =>
+                write!(f, "invalid rank \"{}\" at character {}: expected \"1\"-\"8\"", input, offset),
+            Error::InvalidSquare { input, offset } =>
+                write!(f, "invalid square \"{}\" at character {}: expected \"a1\"-\"h8\"", input, offset),
+            Error::InvalidPieceType { input, offset } =>
+                write!(f, "invalid piece type \"{}\" at character {}: expected one of \"pnbrqk\"", input, offset),
+            Error::InvalidPiece { input, offset } =>
+                write!(f, "invalid piece \"{}\" at character {}: expected one of \"PNBRQKpnbrqk\"", input, offset),
+            Error::InvalidCastlingRights { input, offset } =>
+                write!(f, "invalid castling rights \"{}\" at character {}: expected \"-\" or a subset of \"KQkq\"", input, offset),
+            Error::InvalidMove { input, offset } =>
+                write!(f, "invalid move \"{}\" at character {}: expected UCI notation like \"e2e4\" or \"e7e8q\"", input, offset),
+            Error::IllegalMove { input, offset } =>
+                write!(f, "illegal move \"{}\" at character {}", input, offset),
+            Error::InvalidFen { field, input, offset } =>
+                write!(f, "invalid fen \"{}\" at character {}: bad {}", input, offset, field),
+            Error::InvalidPgn { line, input, offset } =>
+                write!(f, "invalid pgn in line {} \"{}\" at character {}", line, input, offset),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accessors() {
+        let e = Error::InvalidSquare { input: "z9".to_string(), offset: 0 };
+        assert_eq!("z9", e.input());
+        assert_eq!(0, e.offset());
+        let e = Error::InvalidFen { field: FenField::SideToMove, input: "8/8/8/8/8/8/8/8 x".to_string(), offset: 16 };
+        assert_eq!(16, e.offset());
+    }
 
-    #[fail(display = "The string specified does not contain a valid algebraic notation square")]
-    InvalidSquare,
+    #[test]
+    fn test_print() {
+        let e = Error::InvalidSquare { input: "z9".to_string(), offset: 0 };
+        assert_eq!("invalid square \"z9\" at character 0: expected \"a1\"-\"h8\"", e.to_string());
+        let e = Error::InvalidFen { field: FenField::SideToMove, input: "8/8/8/8/8/8/8/8 x".to_string(), offset: 16 };
+        assert_eq!("invalid fen \"8/8/8/8/8/8/8/8 x\" at character 16: bad side to move", e.to_string());
+        let e: Box<dyn std::error::Error> = Box::new(e);
+        assert!(e.source().is_none());
+    }
 
 }
@@ -22,6 +22,9 @@
 use std::fmt;
 use std::str::FromStr;
 use crate::Error;
+use crate::Bitboard;
+use crate::Square;
+use crate::SQUARES;
 
 #[derive(PartialOrd, PartialEq, Eq, Copy, Clone, Debug, Hash)]
 pub enum File {
@@ -52,34 +55,88 @@ impl File {
 
     /// Returns the index of the file starting at 0=A to 7=H.
     #[inline]
-    pub fn to_index(&self) -> usize {
+    pub const fn to_index(&self) -> usize {
         *self as usize
     }
 
-    /// Returns the file enum for the give index modulo 7.
+    /// Returns the file enum for the given index modulo 8.
     /// This means that any square index can be used to get the correct file.
     #[inline]
-    pub fn from_index(i: usize) -> File { FILES[i&7] }
+    pub const fn wrapping_from_index(i: usize) -> File { FILES[i & 7] }
+
+    /// Returns the file for the given index modulo 8 like wrapping_from_index().
+    #[deprecated(note = "wraps around silently, use wrapping_from_index() to keep this \
+                         or try_from() to reject indexes out of range")]
+    #[inline]
+    pub const fn from_index(i: usize) -> File { File::wrapping_from_index(i) }
+
+    /// Returns the file for the given index (0=A to 7=H) or None if the
+    /// index is out of range.
+    #[inline]
+    pub const fn try_from_index(i: usize) -> Option<File> {
+        if i < FILES_LEN { Some(FILES[i]) } else { None }
+    }
+
+    /// Returns the file for an ASCII letter "a"-"h" (any case) or None.
+    #[inline]
+    pub const fn from_ascii(c: u8) -> Option<File> {
+        match c.to_ascii_lowercase() {
+            b'a'..=b'h' => Some(FILES[(c.to_ascii_lowercase() - b'a') as usize]),
+            _ => None
+        }
+    }
+
+    /// Returns an iterator over the squares of this file from rank 1 to rank 8.
+    #[inline]
+    pub fn squares(&self) -> impl DoubleEndedIterator<Item = Square> + ExactSizeIterator {
+        SQUARES[self.to_index()..].iter().step_by(8).copied()
+    }
+
+    /// Returns the Bitboard of the files directly left and right of this file.
+    #[inline]
+    pub fn neighbors(&self) -> Bitboard {
+        let bb = self.bitboard();
+        Bitboard((bb & !Bitboard::FILE_A).0 >> 1 | (bb & !Bitboard::FILE_H).0 << 1)
+    }
+
+    /// Returns the absolute difference of the file indexes, e.g. 1 for adjacent files.
+    #[inline]
+    pub fn distance(&self, other: File) -> u32 { self.to_index().abs_diff(other.to_index()) as u32 }
 
 }
 
 impl FromStr for File {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() < 1 {
-            return Err(Error::InvalidFile);
-        }
-        match s.to_lowercase().chars().next().unwrap() {
-            'a' => Ok(File::A),
-            'b' => Ok(File::B),
-            'c' => Ok(File::C),
-            'd' => Ok(File::D),
-            'e' => Ok(File::E),
-            'f' => Ok(File::F),
-            'g' => Ok(File::G),
-            'h' => Ok(File::H),
-            _ => Err(Error::InvalidFile),
+        let invalid = |offset| Error::InvalidFile { input: s.to_string(), offset };
+        let bytes = s.as_bytes();
+        let file = bytes.first().and_then(|c| File::from_ascii(*c)).ok_or_else(|| invalid(0))?;
+        if bytes.len() > 1 {
+            return Err(invalid(1));
         }
+        Ok(file)
+    }
+}
+
+impl TryFrom<usize> for File {
+    type Error = Error;
+    fn try_from(i: usize) -> Result<Self, Self::Error> {
+        File::try_from_index(i).ok_or_else(|| Error::InvalidFile { input: i.to_string(), offset: 0 })
+    }
+}
+
+impl TryFrom<u8> for File {
+    type Error = Error;
+    fn try_from(i: u8) -> Result<Self, Self::Error> { File::try_from(i as usize) }
+}
+
+/// Converts a file letter "a"-"h" (any case) to a File.
+impl TryFrom<char> for File {
+    type Error = Error;
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        u8::try_from(c).ok()
+            .and_then(File::from_ascii)
+            .ok_or_else(|| Error::InvalidFile { input: c.to_string(), offset: 0 })
     }
 }
 
@@ -113,13 +170,53 @@ mod tests {
     }
 
     #[test]
-    fn test_from_index() {
-        assert_eq!(File::A, File::from_index(0));
-        assert_eq!(File::C, File::from_index(2));
-        assert_eq!(File::H, File::from_index(7));
-        assert_eq!(File::A, File::from_index(8));
-        assert_eq!(File::H, File::from_index(63));
-        assert_eq!(File::A, File::from_index(64));
+    fn test_wrapping_from_index() {
+        assert_eq!(File::A, File::wrapping_from_index(0));
+        assert_eq!(File::C, File::wrapping_from_index(2));
+        assert_eq!(File::H, File::wrapping_from_index(7));
+        assert_eq!(File::A, File::wrapping_from_index(8));
+        assert_eq!(File::H, File::wrapping_from_index(63));
+        assert_eq!(File::A, File::wrapping_from_index(64));
+        #[allow(deprecated)]
+        let old = File::from_index(63);
+        assert_eq!(File::H, old);
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(Some(File::H), File::try_from_index(7));
+        assert_eq!(None, File::try_from_index(8));
+        assert_eq!(Ok(File::C), File::try_from(2usize));
+        assert_eq!(Ok(File::D), File::try_from(3u8));
+        assert_eq!(Err(Error::InvalidFile { input: "8".to_string(), offset: 0 }), File::try_from(8u8));
+        assert_eq!(Ok(File::E), File::try_from('e'));
+        assert_eq!(Ok(File::H), File::try_from('H'));
+        assert_eq!(Err(Error::InvalidFile { input: "i".to_string(), offset: 0 }), File::try_from('i'));
+    }
+
+    #[test]
+    fn test_squares() {
+        let squares: Vec<Square> = File::C.squares().collect();
+        assert_eq!(vec![Square::C1, Square::C2, Square::C3, Square::C4, Square::C5, Square::C6, Square::C7, Square::C8], squares);
+        assert_eq!(8, File::H.squares().len());
+        assert_eq!(Some(Square::A8), File::A.squares().next_back());
+        for f in FILES {
+            assert!(f.squares().all(|sq| sq.file_of() == f));
+        }
+    }
+
+    #[test]
+    fn test_neighbors() {
+        assert_eq!(Bitboard::FILE_B, File::A.neighbors());
+        assert_eq!(Bitboard::FILE_C | Bitboard::FILE_E, File::D.neighbors());
+        assert_eq!(Bitboard::FILE_G, File::H.neighbors());
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(0, File::C.distance(File::C));
+        assert_eq!(7, File::H.distance(File::A));
+        assert_eq!(2, File::E.distance(File::G));
     }
 
     #[test]
@@ -145,8 +242,17 @@ mod tests {
         let result = File::from_str("i");
         match result {
             Ok(_) => panic!("Test failed"),
-            Err(e) => assert_eq!(Error::InvalidFile, e),
+            Err(e) => assert_eq!(Error::InvalidFile { input: "i".to_string(), offset: 0 }, e),
         }
+
+        let result = File::from_str("ab");
+        match result {
+            Ok(_) => panic!("Test failed"),
+            Err(e) => assert_eq!(Error::InvalidFile { input: "ab".to_string(), offset: 1 }, e),
+        }
+
+        assert!(File::from_str("").is_err());
+        assert!(File::from_str("ä").is_err());
     }
 
     #[test]
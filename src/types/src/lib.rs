@@ -10,6 +10,33 @@ pub use crate::rank::*;
 mod square;
 pub use crate::square::*;
 
+mod bitboard;
+pub use crate::bitboard::*;
+
+mod direction;
+pub use crate::direction::*;
+
+mod rays;
+
+mod piece_type;
+pub use crate::piece_type::*;
+
+mod piece;
+pub use crate::piece::*;
+
+mod castling_rights;
+pub use crate::castling_rights::*;
+
+mod moves;
+pub use crate::moves::*;
+
+pub mod attacks;
+
+pub mod zobrist;
+
+#[cfg(feature = "serde")]
+mod serialization;
+
 mod error;
-pub use crate::error::Error;
+pub use crate::error::{Error, FenField};
 
@@ -0,0 +1,299 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use crate::Error;
+use crate::PieceType;
+use crate::Square;
+use crate::PIECE_TYPES;
+
+/// The kind of a move which needs special handling when made on a board.
+#[derive(PartialOrd, PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub enum MoveType {
+    Normal,
+    Promotion,
+    EnPassant,
+    Castling
+}
+
+const MOVE_TYPES: [MoveType; 4] = [
+    MoveType::Normal,
+    MoveType::Promotion,
+    MoveType::EnPassant,
+    MoveType::Castling
+];
+
+/// A move encoded into 16 bits:
+/// bits 0-5 to square, bits 6-11 from square, bits 12-13 promotion piece type
+/// (0=Knight to 3=Queen) and bits 14-15 the MoveType.
+/// Castling moves are encoded as king moves (e.g. e1g1).
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash, Default)]
+pub struct Move(pub u16);
+
+impl Move {
+
+    /// The empty move which is also used as the null move in search (UCI "0000").
+    pub const NULL: Move = Move(0);
+
+    #[inline]
+    fn encode(from: Square, to: Square, promotion: PieceType, move_type: MoveType) -> Move {
+        debug_assert!(promotion != PieceType::Pawn && promotion != PieceType::King);
+        Move((move_type as u16) << 14
+            | ((promotion.index() as u16 - PieceType::Knight.index() as u16) << 12)
+            | (from.to_index() as u16) << 6
+            | to.to_index() as u16)
+    }
+
+    /// Creates a normal move.
+    #[inline]
+    pub fn new(from: Square, to: Square) -> Move {
+        Move::encode(from, to, PieceType::Knight, MoveType::Normal)
+    }
+
+    /// Creates a pawn move promoting to the given piece type (Knight to Queen).
+    #[inline]
+    pub fn new_promotion(from: Square, to: Square, promotion: PieceType) -> Move {
+        Move::encode(from, to, promotion, MoveType::Promotion)
+    }
+
+    /// Creates an en passant capture. The to square is the square the pawn moves to.
+    #[inline]
+    pub fn new_en_passant(from: Square, to: Square) -> Move {
+        Move::encode(from, to, PieceType::Knight, MoveType::EnPassant)
+    }
+
+    /// Creates a castling move given by the king's from and to square.
+    #[inline]
+    pub fn new_castling(from: Square, to: Square) -> Move {
+        Move::encode(from, to, PieceType::Knight, MoveType::Castling)
+    }
+
+    #[inline]
+    pub fn from(&self) -> Square { Square::wrapping_from_index((self.0 >> 6) as usize) }
+
+    #[inline]
+    pub fn to(&self) -> Square { Square::wrapping_from_index(self.0 as usize) }
+
+    #[inline]
+    pub fn move_type(&self) -> MoveType { MOVE_TYPES[(self.0 >> 14) as usize] }
+
+    /// Returns the piece type a pawn promotes to or None if this is not a promotion.
+    #[inline]
+    pub fn promotion(&self) -> Option<PieceType> {
+        match self.move_type() {
+            MoveType::Promotion => Some(PIECE_TYPES[PieceType::Knight.index() + ((self.0 >> 12) & 3) as usize]),
+            _ => None
+        }
+    }
+
+    #[inline]
+    pub fn is_null(&self) -> bool { *self == Move::NULL }
+
+}
+
+impl Move {
+
+    /// Parses a move in UCI long algebraic notation ("e2e4", "e7e8q", "0000")
+    /// from ASCII bytes without allocating. Trailing input is rejected.
+    /// As the board is not known the result is either a normal move or a promotion.
+    /// Castling and en passant moves have to be resolved against a position.
+    /// As for Square::parse_bytes the error offset is also a character offset.
+    pub fn parse_bytes(bytes: &[u8]) -> Result<Move, Error> {
+        if bytes == b"0000" {
+            return Ok(Move::NULL);
+        }
+        let invalid = |offset| Error::InvalidMove { input: String::from_utf8_lossy(bytes).into_owned(), offset };
+        let square = |start: usize| {
+            let end = bytes.len().min(start + 2);
+            Square::parse_bytes(&bytes[start.min(end)..end]).map_err(|e| invalid(start + e.offset()))
+        };
+        let from = square(0)?;
+        let to = square(2)?;
+        if from == to {
+            return Err(invalid(2));
+        }
+        let m = match bytes.get(4).map(u8::to_ascii_lowercase) {
+            None => return Ok(Move::new(from, to)),
+            Some(b'n') => Move::new_promotion(from, to, PieceType::Knight),
+            Some(b'b') => Move::new_promotion(from, to, PieceType::Bishop),
+            Some(b'r') => Move::new_promotion(from, to, PieceType::Rook),
+            Some(b'q') => Move::new_promotion(from, to, PieceType::Queen),
+            Some(_) => return Err(invalid(4)),
+        };
+        if bytes.len() > 5 {
+            return Err(invalid(5));
+        }
+        Ok(m)
+    }
+
+}
+
+/// Parses a move in UCI long algebraic notation. See Move::parse_bytes.
+impl FromStr for Move {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Move::parse_bytes(s.as_bytes())
+    }
+}
+
+/// Prints the move in UCI long algebraic notation.
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_null() {
+            return write!(f, "0000");
+        }
+        write!(f, "{}{}", self.from(), self.to())?;
+        if let Some(pt) = self.promotion() {
+            write!(f, "{}", pt.to_char().to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+/// A move together with a value for move ordering packed into 32 bits.
+/// The upper 16 bits hold the value so scored moves order by their value.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash, Default)]
+pub struct ScoredMove(pub u32);
+
+impl ScoredMove {
+
+    #[inline]
+    pub fn new(m: Move, value: i16) -> ScoredMove {
+        ScoredMove(((value as i32 - i16::MIN as i32) as u32) << 16 | m.0 as u32)
+    }
+
+    /// Returns the move without its value.
+    #[inline]
+    pub fn mv(&self) -> Move { Move(self.0 as u16) }
+
+    #[inline]
+    pub fn value(&self) -> i16 { ((self.0 >> 16) as i32 + i16::MIN as i32) as i16 }
+
+    /// Returns the same move with a new value.
+    #[inline]
+    pub fn with_value(&self, value: i16) -> ScoredMove { ScoredMove::new(self.mv(), value) }
+
+}
+
+impl From<Move> for ScoredMove {
+    #[inline]
+    fn from(m: Move) -> Self { ScoredMove::new(m, 0) }
+}
+
+impl PartialOrd for ScoredMove {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+/// Scored moves are ordered by their value first.
+impl Ord for ScoredMove {
+    fn cmp(&self, other: &Self) -> Ordering { self.0.cmp(&other.0) }
+}
+
+impl fmt::Display for ScoredMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.mv(), self.value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding() {
+        let m = Move::new(Square::E2, Square::E4);
+        assert_eq!(Square::E2, m.from());
+        assert_eq!(Square::E4, m.to());
+        assert_eq!(MoveType::Normal, m.move_type());
+        assert_eq!(None, m.promotion());
+
+        let m = Move::new_promotion(Square::A7, Square::B8, PieceType::Queen);
+        assert_eq!(Square::A7, m.from());
+        assert_eq!(Square::B8, m.to());
+        assert_eq!(MoveType::Promotion, m.move_type());
+        assert_eq!(Some(PieceType::Queen), m.promotion());
+        assert_eq!(Some(PieceType::Knight), Move::new_promotion(Square::H2, Square::H1, PieceType::Knight).promotion());
+
+        assert_eq!(MoveType::EnPassant, Move::new_en_passant(Square::E5, Square::D6).move_type());
+        let m = Move::new_castling(Square::E8, Square::C8);
+        assert_eq!(MoveType::Castling, m.move_type());
+        assert_eq!(Square::E8, m.from());
+        assert_eq!(Square::C8, m.to());
+
+        assert!(Move::NULL.is_null());
+        assert!(!m.is_null());
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Ok(Move::new(Square::E2, Square::E4)), Move::from_str("e2e4"));
+        assert_eq!(Ok(Move::new_promotion(Square::E7, Square::E8, PieceType::Queen)), Move::from_str("e7e8q"));
+        assert_eq!(Ok(Move::new_promotion(Square::B2, Square::A1, PieceType::Rook)), Move::from_str("b2a1r"));
+        assert_eq!(Ok(Move::NULL), Move::from_str("0000"));
+        let invalid = |input: &str, offset| Err(Error::InvalidMove { input: input.to_string(), offset });
+        assert_eq!(invalid("e2", 2), Move::from_str("e2"));
+        assert_eq!(invalid("e2e9", 3), Move::from_str("e2e9"));
+        assert_eq!(invalid("e2x4", 2), Move::from_str("e2x4"));
+        assert_eq!(invalid("e2e2", 2), Move::from_str("e2e2"));
+        assert_eq!(invalid("e7e8k", 4), Move::from_str("e7e8k"));
+        assert_eq!(invalid("e7e8qq", 5), Move::from_str("e7e8qq"));
+        assert_eq!(invalid("e2äe", 2), Move::from_str("e2äe"));
+        assert_eq!(invalid("e2e4 ", 4), Move::from_str("e2e4 "));
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        assert_eq!(Ok(Move::new(Square::G1, Square::F3)), Move::parse_bytes(b"g1f3"));
+        assert_eq!(Ok(Move::new_promotion(Square::A2, Square::A1, PieceType::Bishop)), Move::parse_bytes(b"a2a1b"));
+        assert_eq!(Ok(Move::NULL), Move::parse_bytes(b"0000"));
+        let line = b"e2e4 e7e5 g1f3 b8c6";
+        let moves: Result<Vec<Move>, Error> = line.split(|b| *b == b' ').map(Move::parse_bytes).collect();
+        assert_eq!(4, moves.unwrap().len());
+    }
+
+    #[test]
+    fn test_print() {
+        assert_eq!("e2e4", format!("{}", Move::new(Square::E2, Square::E4)));
+        assert_eq!("e7e8n", format!("{}", Move::new_promotion(Square::E7, Square::E8, PieceType::Knight)));
+        assert_eq!("e1g1", format!("{}", Move::new_castling(Square::E1, Square::G1)));
+        assert_eq!("0000", format!("{}", Move::NULL));
+    }
+
+    #[test]
+    fn test_scored_move() {
+        let m = Move::new(Square::G1, Square::F3);
+        for v in [i16::MIN, -1000, -1, 0, 1, 1000, i16::MAX] {
+            let sm = ScoredMove::new(m, v);
+            assert_eq!(m, sm.mv());
+            assert_eq!(v, sm.value());
+        }
+        let low = ScoredMove::new(Move::new(Square::H8, Square::H7), -5);
+        let high = ScoredMove::new(Move::new(Square::A1, Square::A2), 5);
+        assert!(low < high);
+        assert_eq!(77, low.with_value(77).value());
+        let mut list = vec![high, low, ScoredMove::from(m)];
+        list.sort();
+        assert_eq!(vec![low, ScoredMove::from(m), high], list);
+    }
+
+}
@@ -0,0 +1,185 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::fmt;
+use std::str::FromStr;
+use crate::Color;
+use crate::Error;
+use crate::PieceType;
+use crate::PIECE_TYPES;
+use crate::PIECE_TYPES_LEN;
+use crate::Value;
+
+/// A colored piece. The index of a piece is Color::index() * 6 + PieceType::index().
+#[derive(PartialOrd, PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub enum Piece {
+    WhitePawn,
+    WhiteKnight,
+    WhiteBishop,
+    WhiteRook,
+    WhiteQueen,
+    WhiteKing,
+    BlackPawn,
+    BlackKnight,
+    BlackBishop,
+    BlackRook,
+    BlackQueen,
+    BlackKing
+}
+
+pub const PIECES_LEN: usize = 12;
+
+pub const PIECES: [Piece; PIECES_LEN] = [
+    Piece::WhitePawn,
+    Piece::WhiteKnight,
+    Piece::WhiteBishop,
+    Piece::WhiteRook,
+    Piece::WhiteQueen,
+    Piece::WhiteKing,
+    Piece::BlackPawn,
+    Piece::BlackKnight,
+    Piece::BlackBishop,
+    Piece::BlackRook,
+    Piece::BlackQueen,
+    Piece::BlackKing
+];
+
+impl Piece {
+
+    /// Returns the index of the piece starting at 0=WhitePawn to 11=BlackKing.
+    #[inline]
+    pub fn index(&self) -> usize { *self as usize }
+
+    /// Returns the piece of the given color and type.
+    #[inline]
+    pub fn new(c: Color, pt: PieceType) -> Piece { PIECES[c.index() * PIECE_TYPES_LEN + pt.index()] }
+
+    /// Returns the color of the piece.
+    #[inline]
+    pub fn color_of(&self) -> Color {
+        if self.index() < PIECE_TYPES_LEN { Color::White } else { Color::Black }
+    }
+
+    /// Returns the type of the piece.
+    #[inline]
+    pub fn type_of(&self) -> PieceType { PIECE_TYPES[self.index() % PIECE_TYPES_LEN] }
+
+    /// Returns the conventional material value of the piece in centipawns.
+    #[inline]
+    pub fn value(&self) -> Value { self.type_of().value() }
+
+    /// Returns the FEN letter of the piece - upper case for White, lower case for Black.
+    #[inline]
+    pub fn to_char(&self) -> char {
+        match self.color_of() {
+            Color::White => self.type_of().to_char(),
+            Color::Black => self.type_of().to_char().to_ascii_lowercase()
+        }
+    }
+
+    /// Returns the Unicode chess figurine of the piece.
+    #[inline]
+    pub fn unicode(&self) -> char {
+        match *self {
+            Piece::WhitePawn => '♙',
+            Piece::WhiteKnight => '♘',
+            Piece::WhiteBishop => '♗',
+            Piece::WhiteRook => '♖',
+            Piece::WhiteQueen => '♕',
+            Piece::WhiteKing => '♔',
+            Piece::BlackPawn => '♟',
+            Piece::BlackKnight => '♞',
+            Piece::BlackBishop => '♝',
+            Piece::BlackRook => '♜',
+            Piece::BlackQueen => '♛',
+            Piece::BlackKing => '♚'
+        }
+    }
+
+}
+
+/// Parses a FEN piece letter - upper case for White, lower case for Black.
+impl FromStr for Piece {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pt = PieceType::from_str(s)
+            .map_err(|e| Error::InvalidPiece { input: s.to_string(), offset: e.offset() })?;
+        if s.starts_with(|c: char| c.is_ascii_uppercase()) {
+            Ok(Piece::new(Color::White, pt))
+        } else {
+            Ok(Piece::new(Color::Black, pt))
+        }
+    }
+}
+
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::COLORS;
+
+    #[test]
+    fn test_index() {
+        assert_eq!(0, Piece::WhitePawn.index());
+        assert_eq!(11, Piece::BlackKing.index());
+        for c in COLORS {
+            for pt in PIECE_TYPES {
+                let p = Piece::new(c, pt);
+                assert_eq!(c.index() * PIECE_TYPES_LEN + pt.index(), p.index());
+                assert_eq!(c, p.color_of());
+                assert_eq!(pt, p.type_of());
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Ok(Piece::WhitePawn), Piece::from_str("P"));
+        assert_eq!(Ok(Piece::BlackKnight), Piece::from_str("n"));
+        assert_eq!(Ok(Piece::WhiteKing), Piece::from_str("K"));
+        assert_eq!(Err(Error::InvalidPiece { input: "x".to_string(), offset: 0 }), Piece::from_str("x"));
+        assert_eq!(Err(Error::InvalidPiece { input: "".to_string(), offset: 0 }), Piece::from_str(""));
+        assert_eq!(Err(Error::InvalidPiece { input: "Kk".to_string(), offset: 1 }), Piece::from_str("Kk"));
+        for p in PIECES {
+            assert_eq!(Ok(p), Piece::from_str(&p.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_print() {
+        assert_eq!("P", format!("{}", Piece::WhitePawn));
+        assert_eq!("q", format!("{}", Piece::BlackQueen));
+        assert_eq!('♔', Piece::WhiteKing.unicode());
+        assert_eq!('♟', Piece::BlackPawn.unicode());
+    }
+
+    #[test]
+    fn test_value() {
+        assert_eq!(Piece::WhiteRook.value(), Piece::BlackRook.value());
+        assert_eq!(900, Piece::BlackQueen.value());
+    }
+
+}
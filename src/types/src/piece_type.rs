@@ -0,0 +1,153 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::fmt;
+use std::str::FromStr;
+use crate::Error;
+
+#[derive(PartialOrd, PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub enum PieceType {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King
+}
+
+pub const PIECE_TYPES_LEN: usize = 6;
+
+pub const PIECE_TYPES: [PieceType; PIECE_TYPES_LEN] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+    PieceType::King
+];
+
+/// A material value or score in centipawns.
+pub type Value = i32;
+
+impl PieceType {
+
+    /// Returns the index of the piece type starting at 0=Pawn to 5=King.
+    #[inline]
+    pub fn index(&self) -> usize { *self as usize }
+
+    /// Returns the conventional material value in centipawns.
+    #[inline]
+    pub fn value(&self) -> Value {
+        match *self {
+            PieceType::Pawn => 100,
+            PieceType::Knight => 320,
+            PieceType::Bishop => 330,
+            PieceType::Rook => 500,
+            PieceType::Queen => 900,
+            PieceType::King => 2000
+        }
+    }
+
+    /// Returns the upper case letter of the piece type ("P", "N", ... "K").
+    #[inline]
+    pub fn to_char(&self) -> char {
+        match *self {
+            PieceType::Pawn => 'P',
+            PieceType::Knight => 'N',
+            PieceType::Bishop => 'B',
+            PieceType::Rook => 'R',
+            PieceType::Queen => 'Q',
+            PieceType::King => 'K'
+        }
+    }
+
+    /// Returns true for bishops, rooks and queens.
+    #[inline]
+    pub fn is_slider(&self) -> bool {
+        matches!(*self, PieceType::Bishop | PieceType::Rook | PieceType::Queen)
+    }
+
+}
+
+/// Parses a piece type letter. Upper and lower case are accepted.
+impl FromStr for PieceType {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |offset| Error::InvalidPieceType { input: s.to_string(), offset };
+        let mut chars = s.chars();
+        let pt = match chars.next() {
+            Some('p') | Some('P') => PieceType::Pawn,
+            Some('n') | Some('N') => PieceType::Knight,
+            Some('b') | Some('B') => PieceType::Bishop,
+            Some('r') | Some('R') => PieceType::Rook,
+            Some('q') | Some('Q') => PieceType::Queen,
+            Some('k') | Some('K') => PieceType::King,
+            _ => return Err(invalid(0)),
+        };
+        match chars.next() {
+            None => Ok(pt),
+            Some(_) => Err(invalid(1)),
+        }
+    }
+}
+
+impl fmt::Display for PieceType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index() {
+        assert_eq!(0, PieceType::Pawn.index());
+        assert_eq!(5, PieceType::King.index());
+        for (i, pt) in PIECE_TYPES.iter().enumerate() {
+            assert_eq!(i, pt.index());
+        }
+    }
+
+    #[test]
+    fn test_value() {
+        assert_eq!(100, PieceType::Pawn.value());
+        assert!(PieceType::Knight.value() < PieceType::Bishop.value());
+        assert!(PieceType::Rook.value() < PieceType::Queen.value());
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Ok(PieceType::Knight), PieceType::from_str("N"));
+        assert_eq!(Ok(PieceType::Queen), PieceType::from_str("q"));
+        assert_eq!(Err(Error::InvalidPieceType { input: "x".to_string(), offset: 0 }), PieceType::from_str("x"));
+        assert_eq!(Err(Error::InvalidPieceType { input: "".to_string(), offset: 0 }), PieceType::from_str(""));
+        assert_eq!(Err(Error::InvalidPieceType { input: "Qq".to_string(), offset: 1 }), PieceType::from_str("Qq"));
+    }
+
+    #[test]
+    fn test_print() {
+        assert_eq!("P", format!("{}", PieceType::Pawn));
+        assert_eq!("K", format!("{}", PieceType::King));
+    }
+
+}
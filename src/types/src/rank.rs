@@ -22,6 +22,9 @@
 use std::fmt;
 use std::str::FromStr;
 use crate::Error;
+use crate::Color;
+use crate::Square;
+use crate::SQUARES;
 
 #[derive(PartialOrd, PartialEq, Eq, Copy, Clone, Debug, Hash)]
 pub enum Rank {
@@ -52,34 +55,109 @@ impl Rank {
 
     /// Returns the index of the rank starting at 0=one to 7=eight.
     #[inline]
-    pub fn to_index(&self) -> usize {
+    pub const fn to_index(&self) -> usize {
         *self as usize
     }
 
-    /// Returns the Rank for the given index modulo 7.
-    /// This means if the index is greater than 63 it is wrapped around.
+    /// Returns the Rank for the given rank index modulo 8.
+    /// This means if the index is greater than 7 it is wrapped around.
+    /// For the rank of a square index use from_square_index().
     #[inline]
-    pub fn from_index(i: usize) -> Rank { RANKS[(i >> 3) & 7] }
+    pub const fn wrapping_from_index(i: usize) -> Rank { RANKS[i & 7] }
+
+    /// Returns the rank of the given square index (0=a1 to 63=h8) modulo 64.
+    #[inline]
+    pub const fn from_square_index(i: usize) -> Rank { RANKS[(i >> 3) & 7] }
+
+    /// Returns the rank of the given square index like from_square_index().
+    #[deprecated(note = "takes a square index, use from_square_index() for square indexes \
+                         or wrapping_from_index() for rank indexes")]
+    #[inline]
+    pub const fn from_index(i: usize) -> Rank { Rank::from_square_index(i) }
+
+    /// Returns the rank for the given index (0=One to 7=Eight) or None if the
+    /// index is out of range.
+    #[inline]
+    pub const fn try_from_index(i: usize) -> Option<Rank> {
+        if i < RANKS_LEN { Some(RANKS[i]) } else { None }
+    }
+
+    /// Returns the rank for an ASCII digit "1"-"8" or None.
+    #[inline]
+    pub const fn from_ascii(c: u8) -> Option<Rank> {
+        match c {
+            b'1'..=b'8' => Some(RANKS[(c - b'1') as usize]),
+            _ => None
+        }
+    }
+
+    /// Returns an iterator over the squares of this rank from file a to file h.
+    #[inline]
+    pub fn squares(&self) -> impl DoubleEndedIterator<Item = Square> + ExactSizeIterator {
+        let start = self.to_index() * 8;
+        SQUARES[start..start + 8].iter().copied()
+    }
+
+    /// Returns the rank as seen from the given color's side of the board.
+    /// For White this is the rank itself, for Black Rank::One becomes Rank::Eight etc.
+    #[inline]
+    pub fn relative_to(&self, c: Color) -> Rank {
+        match c {
+            Color::White => *self,
+            Color::Black => RANKS[7 - self.to_index()]
+        }
+    }
+
+    /// Returns the rank n steps ahead from the given color's point of view
+    /// or None if this would leave the board.
+    #[inline]
+    pub fn forward(&self, c: Color, n: u8) -> Option<Rank> {
+        let r = self.to_index() as i32 + c.direction() * i32::from(n);
+        if (0..8).contains(&r) {
+            Some(RANKS[r as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the absolute difference of the rank indexes, e.g. 1 for adjacent ranks.
+    #[inline]
+    pub fn distance(&self, other: Rank) -> u32 { self.to_index().abs_diff(other.to_index()) as u32 }
 
 }
 
 impl FromStr for Rank {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() < 1 {
-            return Err(Error::InvalidRank);
-        }
-        match s.chars().next().unwrap() {
-            '1' => Ok(Rank::One  ),
-            '2' => Ok(Rank::Two  ),
-            '3' => Ok(Rank::Three),
-            '4' => Ok(Rank::Four ),
-            '5' => Ok(Rank::Five ),
-            '6' => Ok(Rank::Six  ),
-            '7' => Ok(Rank::Seven),
-            '8' => Ok(Rank::Eight),
-            _ => Err(Error::InvalidRank),
+        let invalid = |offset| Error::InvalidRank { input: s.to_string(), offset };
+        let bytes = s.as_bytes();
+        let rank = bytes.first().and_then(|c| Rank::from_ascii(*c)).ok_or_else(|| invalid(0))?;
+        if bytes.len() > 1 {
+            return Err(invalid(1));
         }
+        Ok(rank)
+    }
+}
+
+impl TryFrom<usize> for Rank {
+    type Error = Error;
+    fn try_from(i: usize) -> Result<Self, Self::Error> {
+        Rank::try_from_index(i).ok_or_else(|| Error::InvalidRank { input: i.to_string(), offset: 0 })
+    }
+}
+
+impl TryFrom<u8> for Rank {
+    type Error = Error;
+    fn try_from(i: u8) -> Result<Self, Self::Error> { Rank::try_from(i as usize) }
+}
+
+/// Converts a rank digit "1"-"8" to a Rank.
+impl TryFrom<char> for Rank {
+    type Error = Error;
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        u8::try_from(c).ok()
+            .and_then(Rank::from_ascii)
+            .ok_or_else(|| Error::InvalidRank { input: c.to_string(), offset: 0 })
     }
 }
 
@@ -104,7 +182,6 @@ impl fmt::Display for Rank {
 
 #[cfg(test)]
 mod tests {
-    use crate::Square;
     use super::*;
 
     #[test]
@@ -114,14 +191,79 @@ mod tests {
     }
 
     #[test]
-    fn test_from_index() {
-        assert_eq!(Rank::One, Rank::from_index(0));
-        assert_eq!(Rank::One, Rank::from_index(7));
-        assert_eq!(Rank::Three, Rank::from_index(16));
-        assert_eq!(Rank::Three, Rank::from_index(23));
-        assert_eq!(Rank::Eight, Rank::from_index(56));
-        assert_eq!(Rank::Eight, Rank::from_index(63));
-        assert_eq!(Rank::One, Rank::from_index(64));
+    fn test_from_square_index() {
+        assert_eq!(Rank::One, Rank::from_square_index(0));
+        assert_eq!(Rank::One, Rank::from_square_index(7));
+        assert_eq!(Rank::Three, Rank::from_square_index(16));
+        assert_eq!(Rank::Three, Rank::from_square_index(23));
+        assert_eq!(Rank::Eight, Rank::from_square_index(56));
+        assert_eq!(Rank::Eight, Rank::from_square_index(63));
+        assert_eq!(Rank::One, Rank::from_square_index(64));
+        #[allow(deprecated)]
+        let old = Rank::from_index(23);
+        assert_eq!(Rank::Three, old);
+        // wrapping_from_index takes a rank index, not a square index
+        assert_eq!(Rank::Eight, Rank::wrapping_from_index(23));
+    }
+
+    #[test]
+    fn test_wrapping_from_index() {
+        assert_eq!(Rank::One, Rank::wrapping_from_index(0));
+        assert_eq!(Rank::Three, Rank::wrapping_from_index(2));
+        assert_eq!(Rank::Eight, Rank::wrapping_from_index(7));
+        assert_eq!(Rank::One, Rank::wrapping_from_index(8));
+        assert_eq!(Rank::Eight, Rank::wrapping_from_index(63));
+        assert_eq!(Rank::One, Rank::wrapping_from_index(64));
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(Some(Rank::Eight), Rank::try_from_index(7));
+        assert_eq!(None, Rank::try_from_index(8));
+        assert_eq!(Ok(Rank::Three), Rank::try_from(2usize));
+        assert_eq!(Ok(Rank::Four), Rank::try_from(3u8));
+        assert_eq!(Err(Error::InvalidRank { input: "16".to_string(), offset: 0 }), Rank::try_from(16usize));
+        assert_eq!(Ok(Rank::Five), Rank::try_from('5'));
+        assert_eq!(Err(Error::InvalidRank { input: "0".to_string(), offset: 0 }), Rank::try_from('0'));
+    }
+
+    #[test]
+    fn test_squares() {
+        let squares: Vec<Square> = Rank::Two.squares().collect();
+        assert_eq!(vec![Square::A2, Square::B2, Square::C2, Square::D2, Square::E2, Square::F2, Square::G2, Square::H2], squares);
+        assert_eq!(8, Rank::Eight.squares().len());
+        assert_eq!(Some(Square::H1), Rank::One.squares().next_back());
+        for r in RANKS {
+            assert!(r.squares().all(|sq| sq.rank_of() == r));
+        }
+    }
+
+    #[test]
+    fn test_relative_to() {
+        assert_eq!(Rank::Two, Rank::Two.relative_to(Color::White));
+        assert_eq!(Rank::Seven, Rank::Two.relative_to(Color::Black));
+        assert_eq!(Rank::One, Rank::Eight.relative_to(Color::Black));
+        for r in RANKS {
+            assert_eq!(r, r.relative_to(Color::Black).relative_to(Color::Black));
+        }
+    }
+
+    #[test]
+    fn test_forward() {
+        assert_eq!(Some(Rank::Three), Rank::Two.forward(Color::White, 1));
+        assert_eq!(Some(Rank::Four), Rank::Two.forward(Color::White, 2));
+        assert_eq!(Some(Rank::Five), Rank::Seven.forward(Color::Black, 2));
+        assert_eq!(Some(Rank::Seven), Rank::Seven.forward(Color::Black, 0));
+        assert_eq!(None, Rank::Eight.forward(Color::White, 1));
+        assert_eq!(None, Rank::Two.forward(Color::Black, 2));
+        assert_eq!(None, Rank::One.forward(Color::White, u8::MAX));
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(0, Rank::Four.distance(Rank::Four));
+        assert_eq!(7, Rank::One.distance(Rank::Eight));
+        assert_eq!(3, Rank::Six.distance(Rank::Three));
     }
 
     #[test]
@@ -147,7 +289,13 @@ mod tests {
         let result = Rank::from_str("9");
         match result {
             Ok(_) => panic!("Test failed"),
-            Err(e) => assert_eq!(Error::InvalidRank, e),
+            Err(e) => assert_eq!(Error::InvalidRank { input: "9".to_string(), offset: 0 }, e),
+        }
+
+        let result = Rank::from_str("12");
+        match result {
+            Ok(_) => panic!("Test failed"),
+            Err(e) => assert_eq!(Error::InvalidRank { input: "12".to_string(), offset: 1 }, e),
         }
     }
 
@@ -0,0 +1,243 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::attacks;
+use crate::Bitboard;
+use crate::Direction;
+use crate::DIRECTIONS;
+use crate::DIRECTIONS_LEN;
+use crate::KNIGHT_DIRECTIONS;
+use crate::Square;
+use crate::SQUARES_LEN;
+
+/// Rays for every direction (indexed by the Direction discriminant) and
+/// every square. A ray does not contain its origin.
+static RAYS: [[Bitboard; SQUARES_LEN]; 2 * DIRECTIONS_LEN] = init_rays();
+
+/// Squares strictly between two aligned squares, empty if not aligned.
+static BETWEEN: [[Bitboard; SQUARES_LEN]; SQUARES_LEN] = init_between_and_line().0;
+
+/// Full line through two aligned squares from edge to edge, empty if not aligned.
+static LINE: [[Bitboard; SQUARES_LEN]; SQUARES_LEN] = init_between_and_line().1;
+
+const fn ray(sq: usize, dir: Direction) -> u64 {
+    let mut bb = 0u64;
+    let mut file = (sq & 7) as i32 + dir.file_delta();
+    let mut rank = (sq >> 3) as i32 + dir.rank_delta();
+    while file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+        bb |= 1 << (rank * 8 + file);
+        file += dir.file_delta();
+        rank += dir.rank_delta();
+    }
+    bb
+}
+
+const fn init_rays() -> [[Bitboard; SQUARES_LEN]; 2 * DIRECTIONS_LEN] {
+    let mut rays = [[Bitboard::EMPTY; SQUARES_LEN]; 2 * DIRECTIONS_LEN];
+    let mut d = 0;
+    while d < DIRECTIONS_LEN {
+        let mut sq = 0;
+        while sq < SQUARES_LEN {
+            rays[DIRECTIONS[d] as usize][sq] = Bitboard(ray(sq, DIRECTIONS[d]));
+            rays[KNIGHT_DIRECTIONS[d] as usize][sq] = Bitboard(ray(sq, KNIGHT_DIRECTIONS[d]));
+            sq += 1;
+        }
+        d += 1;
+    }
+    rays
+}
+
+#[allow(clippy::type_complexity)]
+const fn init_between_and_line() -> ([[Bitboard; SQUARES_LEN]; SQUARES_LEN], [[Bitboard; SQUARES_LEN]; SQUARES_LEN]) {
+    let mut between = [[Bitboard::EMPTY; SQUARES_LEN]; SQUARES_LEN];
+    let mut line = [[Bitboard::EMPTY; SQUARES_LEN]; SQUARES_LEN];
+    let mut from = 0;
+    while from < SQUARES_LEN {
+        let mut d = 0;
+        while d < DIRECTIONS_LEN {
+            let dir = DIRECTIONS[d];
+            let full = ray(from, dir) | ray(from, dir.opposite()) | (1 << from);
+            let mut file = (from & 7) as i32 + dir.file_delta();
+            let mut rank = (from >> 3) as i32 + dir.rank_delta();
+            let mut passed = 0u64;
+            while file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                let to = (rank * 8 + file) as usize;
+                between[from][to] = Bitboard(passed);
+                line[from][to] = Bitboard(full);
+                passed |= 1 << to;
+                file += dir.file_delta();
+                rank += dir.rank_delta();
+            }
+            d += 1;
+        }
+        from += 1;
+    }
+    (between, line)
+}
+
+impl Square {
+
+    /// Returns all squares from this square (exclusive) to the edge of the
+    /// board in the given direction.
+    #[inline]
+    pub fn ray(&self, dir: Direction) -> Bitboard { RAYS[dir as usize][self.to_index()] }
+
+    /// Returns the squares strictly between this and the other square if both
+    /// are on the same rank, file or diagonal. Otherwise the set is empty.
+    #[inline]
+    pub fn between(&self, other: Square) -> Bitboard { BETWEEN[self.to_index()][other.to_index()] }
+
+    /// Returns the full line (rank, file or diagonal) from edge to edge through
+    /// this and the other square. If they are not aligned the set is empty.
+    #[inline]
+    pub fn line(&self, other: Square) -> Bitboard { LINE[self.to_index()][other.to_index()] }
+
+    /// Returns all squares on the a1-h8 oriented diagonal through this square
+    /// (including the square itself).
+    #[inline]
+    pub fn diagonal(&self) -> Bitboard {
+        self.ray(Direction::NorthEast) | self.ray(Direction::SouthWest) | self.bitboard()
+    }
+
+    /// Returns all squares on the h1-a8 oriented diagonal through this square
+    /// (including the square itself).
+    #[inline]
+    pub fn anti_diagonal(&self) -> Bitboard {
+        self.ray(Direction::NorthWest) | self.ray(Direction::SouthEast) | self.bitboard()
+    }
+
+    /// Returns the squares directly surrounding this square (the king zone)
+    /// without the square itself.
+    #[inline]
+    pub fn king_ring(&self) -> Bitboard {
+        attacks::king(*self)
+    }
+
+    /// Returns true if the three squares are on one common rank, file or diagonal.
+    #[inline]
+    pub fn aligned(a: Square, b: Square, c: Square) -> bool { a.line(b).has(c) }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SQUARES;
+
+    /// walks from a square into a direction and collects all squares
+    fn walk(from: Square, dir: Direction) -> Vec<Square> {
+        let mut squares = Vec::new();
+        let mut sq = from;
+        while let Some(next) = sq.offset(dir) {
+            squares.push(next);
+            sq = next;
+        }
+        squares
+    }
+
+    fn to_bb(squares: &[Square]) -> Bitboard {
+        squares.iter().fold(Bitboard::EMPTY, |bb, sq| bb | Bitboard::from(*sq))
+    }
+
+    #[test]
+    fn test_ray() {
+        assert_eq!(Bitboard::FILE_E & !Bitboard::RANK_1 & !Bitboard::RANK_2 & !Bitboard::RANK_3 & !Bitboard::RANK_4,
+                   Square::E4.ray(Direction::North));
+        for d in DIRECTIONS.iter().chain(KNIGHT_DIRECTIONS.iter()) {
+            for sq in SQUARES {
+                assert_eq!(to_bb(&walk(sq, *d)), sq.ray(*d));
+            }
+        }
+    }
+
+    #[test]
+    fn test_between_and_line() {
+        assert_eq!(to_bb(&[Square::B2, Square::C3, Square::D4]), Square::A1.between(Square::E5));
+        assert_eq!(Bitboard::EMPTY, Square::A1.between(Square::B3));
+        assert_eq!(Bitboard::EMPTY, Square::A1.between(Square::B2));
+        assert_eq!(Bitboard::RANK_4, Square::B4.line(Square::G4));
+        assert_eq!(Bitboard::EMPTY, Square::B4.line(Square::G5));
+        for a in SQUARES {
+            for b in SQUARES {
+                let mut between = Bitboard::EMPTY;
+                let mut line = Bitboard::EMPTY;
+                for d in DIRECTIONS {
+                    let squares = walk(a, d);
+                    if let Some(pos) = squares.iter().position(|s| *s == b) {
+                        between = to_bb(&squares[..pos]);
+                        line = to_bb(&squares) | to_bb(&walk(a, d.opposite())) | Bitboard::from(a);
+                    }
+                }
+                assert_eq!(between, a.between(b), "{} {}", a, b);
+                assert_eq!(between, b.between(a), "{} {}", a, b);
+                assert_eq!(line, a.line(b), "{} {}", a, b);
+                assert_eq!(line, b.line(a), "{} {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_diagonals() {
+        let squares: Vec<Square> = Square::E5.diagonal().collect();
+        assert_eq!(vec![Square::A1, Square::B2, Square::C3, Square::D4, Square::E5, Square::F6, Square::G7, Square::H8], squares);
+        let squares: Vec<Square> = Square::C1.anti_diagonal().collect();
+        assert_eq!(vec![Square::C1, Square::B2, Square::A3], squares);
+        assert_eq!(to_bb(&[Square::H1]), Square::H1.diagonal());
+        for sq in SQUARES {
+            let file = sq.file_of().to_index() as i32;
+            let rank = sq.rank_of().to_index() as i32;
+            for s in SQUARES {
+                let df = s.file_of().to_index() as i32 - file;
+                let dr = s.rank_of().to_index() as i32 - rank;
+                assert_eq!(df == dr, sq.diagonal().has(s));
+                assert_eq!(df == -dr, sq.anti_diagonal().has(s));
+            }
+            assert_eq!(sq.diagonal() & sq.anti_diagonal(), sq.bitboard());
+        }
+    }
+
+    #[test]
+    fn test_king_ring() {
+        assert_eq!(to_bb(&[Square::F1, Square::H1, Square::F2, Square::G2, Square::H2]), Square::G1.king_ring());
+        assert_eq!(8, Square::E4.king_ring().popcount());
+        assert_eq!(3, Square::A8.king_ring().popcount());
+        for sq in SQUARES {
+            assert!(sq.king_ring().all(|s| s.distance(sq) == 1));
+        }
+    }
+
+    #[test]
+    fn test_aligned() {
+        assert!(Square::aligned(Square::A1, Square::D4, Square::H8));
+        assert!(Square::aligned(Square::A1, Square::H8, Square::D4));
+        assert!(Square::aligned(Square::B2, Square::B7, Square::B1));
+        assert!(!Square::aligned(Square::A1, Square::D4, Square::H7));
+        assert!(!Square::aligned(Square::A1, Square::B3, Square::C5));
+        for a in SQUARES {
+            for b in SQUARES {
+                for c in a.between(b) {
+                    assert!(Square::aligned(a, b, c));
+                }
+            }
+        }
+    }
+
+}
@@ -0,0 +1,178 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Serialize and Deserialize for the core types (cargo feature "serde").
+//! Human readable formats like JSON use the same notation as Display and
+//! FromStr ("w", "e4", "KQkq"). Binary formats use the index of the value
+//! as a compact integer. Bitboards and moves are integers in all formats, as
+//! the UCI notation of a move can't tell castling and en passant apart from
+//! normal moves.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::*;
+
+/// Deserializes a value from its string notation using FromStr.
+struct StrVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for StrVisitor<T>
+    where T: FromStr<Err = Error> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a string in the notation of {}", std::any::type_name::<T>())
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        T::from_str(v).map_err(E::custom)
+    }
+}
+
+macro_rules! impl_serde {
+    ($t:ty, $int:ty, $to_int:expr, $from_int:expr) => {
+        impl Serialize for $t {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                if serializer.is_human_readable() {
+                    serializer.collect_str(self)
+                } else {
+                    let to_int: fn(&$t) -> $int = $to_int;
+                    to_int(self).serialize(serializer)
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $t {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                if deserializer.is_human_readable() {
+                    deserializer.deserialize_str(StrVisitor::<$t>(PhantomData))
+                } else {
+                    let i = <$int>::deserialize(deserializer)?;
+                    let from_int: fn($int) -> Option<$t> = $from_int;
+                    from_int(i).ok_or_else(|| de::Error::custom(
+                        format!("invalid index {} for {}", i, stringify!($t))))
+                }
+            }
+        }
+    };
+}
+
+impl_serde!(Color, u8, |c| c.index() as u8, |i| Color::try_from_index(i as usize));
+impl_serde!(File, u8, |f| f.to_index() as u8, |i| File::try_from_index(i as usize));
+impl_serde!(Rank, u8, |r| r.to_index() as u8, |i| Rank::try_from_index(i as usize));
+impl_serde!(Square, u8, |sq| sq.to_index() as u8, |i| Square::try_from_index(i as usize));
+impl_serde!(PieceType, u8, |pt| pt.index() as u8, |i| PIECE_TYPES.get(i as usize).copied());
+impl_serde!(Piece, u8, |p| p.index() as u8, |i| PIECES.get(i as usize).copied());
+impl_serde!(CastlingRights, u8, |cr| cr.bits(), |i| CastlingRights::try_from_bits(i));
+
+/// Bitboards have no textual notation and are always serialized as u64.
+impl Serialize for Bitboard {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bitboard {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u64::deserialize(deserializer).map(Bitboard)
+    }
+}
+
+/// Moves are always serialized as their u16 encoding which keeps the move type.
+impl Serialize for Move {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Move {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u16::deserialize(deserializer).map(Move)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+
+    fn round_trip<T>(value: T, json: &str)
+        where T: Serialize + DeserializeOwned + PartialEq + fmt::Debug {
+        assert_eq!(json, serde_json::to_string(&value).unwrap());
+        assert_eq!(value, serde_json::from_str::<T>(json).unwrap());
+        let bytes = bincode::serialize(&value).unwrap();
+        assert_eq!(value, bincode::deserialize::<T>(&bytes).unwrap());
+    }
+
+    #[test]
+    fn test_json() {
+        round_trip(Color::White, "\"w\"");
+        round_trip(File::C, "\"c\"");
+        round_trip(Rank::Seven, "\"7\"");
+        round_trip(Square::E4, "\"e4\"");
+        round_trip(PieceType::Rook, "\"R\"");
+        round_trip(Piece::BlackQueen, "\"q\"");
+        round_trip(CastlingRights::WHITE | CastlingRights::BLACK_OO, "\"KQk\"");
+        round_trip(CastlingRights::NONE, "\"-\"");
+        let m = Move::new_promotion(Square::E7, Square::E8, PieceType::Queen);
+        round_trip(m, &m.0.to_string());
+        let m = Move::new_castling(Square::E1, Square::G1);
+        round_trip(m, &m.0.to_string());
+        assert_eq!(MoveType::Castling, serde_json::from_str::<Move>(&m.0.to_string()).unwrap().move_type());
+        let m = Move::new_en_passant(Square::E5, Square::D6);
+        round_trip(m, &m.0.to_string());
+        assert_eq!(MoveType::EnPassant, serde_json::from_str::<Move>(&m.0.to_string()).unwrap().move_type());
+        round_trip(Bitboard::RANK_1, "255");
+        round_trip(vec![Square::A1, Square::H8], "[\"a1\",\"h8\"]");
+    }
+
+    #[test]
+    fn test_binary() {
+        assert_eq!(vec![28], bincode::serialize(&Square::E4).unwrap());
+        assert_eq!(vec![1], bincode::serialize(&Color::Black).unwrap());
+        assert!(bincode::deserialize::<Square>(&[64]).is_err());
+        assert!(bincode::deserialize::<CastlingRights>(&[16]).is_err());
+        for sq in SQUARES {
+            let bytes = bincode::serialize(&sq).unwrap();
+            assert_eq!(sq, bincode::deserialize::<Square>(&bytes).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        let e = serde_json::from_str::<Square>("\"z9\"").unwrap_err();
+        assert!(e.to_string().contains("invalid square \"z9\""));
+        assert!(serde_json::from_str::<Color>("\"x\"").is_err());
+        assert!(serde_json::from_str::<Color>("0").is_err());
+    }
+
+    #[test]
+    fn test_error() {
+        let e = Error::InvalidFen { field: FenField::EnPassant, input: "x".to_string(), offset: 3 };
+        let json = serde_json::to_string(&e).unwrap();
+        assert_eq!(e, serde_json::from_str::<Error>(&json).unwrap());
+        let bytes = bincode::serialize(&e).unwrap();
+        assert_eq!(e, bincode::deserialize::<Error>(&bytes).unwrap());
+    }
+
+}
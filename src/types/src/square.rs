@@ -24,6 +24,7 @@ use std::fmt;
 use std::str::FromStr;
 use crate::File;
 use crate::Rank;
+use crate::Color;
 
 #[derive(PartialOrd, PartialEq, Eq, Copy, Clone, Debug, Hash)]
 pub enum Square {
@@ -162,62 +163,217 @@ pub const SQUARES: [Square; SQUARES_LEN] = [
     Square::H8
 ];
 
+/// Chebyshev (king move) distance between two squares
+static DISTANCE: [[u8; SQUARES_LEN]; SQUARES_LEN] = init_distance(false);
+
+/// Manhattan (rook step) distance between two squares
+static MANHATTAN_DISTANCE: [[u8; SQUARES_LEN]; SQUARES_LEN] = init_distance(true);
+
+/// Chebyshev distance of every square to the nearest of the four center squares
+static CENTER_DISTANCE: [u8; SQUARES_LEN] = init_center_distance();
+
+/// Number of king steps from every square to the nearest edge of the board
+static EDGE_DISTANCE: [u8; SQUARES_LEN] = init_edge_distance();
+
+const fn init_distance(manhattan: bool) -> [[u8; SQUARES_LEN]; SQUARES_LEN] {
+    let mut table = [[0u8; SQUARES_LEN]; SQUARES_LEN];
+    let mut a = 0;
+    while a < SQUARES_LEN {
+        let mut b = 0;
+        while b < SQUARES_LEN {
+            let file_dist = (a & 7).abs_diff(b & 7);
+            let rank_dist = (a >> 3).abs_diff(b >> 3);
+            table[a][b] = if manhattan {
+                file_dist + rank_dist
+            } else if file_dist > rank_dist {
+                file_dist
+            } else {
+                rank_dist
+            } as u8;
+            b += 1;
+        }
+        a += 1;
+    }
+    table
+}
+
+const fn init_center_distance() -> [u8; SQUARES_LEN] {
+    let distance = init_distance(false);
+    let mut table = [0u8; SQUARES_LEN];
+    let mut sq = 0;
+    while sq < SQUARES_LEN {
+        let mut min = distance[sq][Square::D4 as usize];
+        if distance[sq][Square::E4 as usize] < min { min = distance[sq][Square::E4 as usize]; }
+        if distance[sq][Square::D5 as usize] < min { min = distance[sq][Square::D5 as usize]; }
+        if distance[sq][Square::E5 as usize] < min { min = distance[sq][Square::E5 as usize]; }
+        table[sq] = min;
+        sq += 1;
+    }
+    table
+}
+
+const fn init_edge_distance() -> [u8; SQUARES_LEN] {
+    let mut table = [0u8; SQUARES_LEN];
+    let mut sq = 0;
+    while sq < SQUARES_LEN {
+        let file = sq & 7;
+        let rank = sq >> 3;
+        let file_dist = if file < 4 { file } else { 7 - file };
+        let rank_dist = if rank < 4 { rank } else { 7 - rank };
+        table[sq] = if file_dist < rank_dist { file_dist } else { rank_dist } as u8;
+        sq += 1;
+    }
+    table
+}
+
 impl Square {
 
     /// Returns the index of the rank starting at 0=one to 7=eight.
     #[inline]
-    pub fn to_index(&self) -> usize { *self as usize }
+    pub const fn to_index(&self) -> usize { *self as usize }
 
-    /// Returns the Rank for the given index modulo 63.
+    /// Returns the Square for the given index modulo 64.
     /// This means if the index is greater than 63 it is wrapped around.
     #[inline]
-    pub fn from_index(i: usize) -> Square { SQUARES[i&63] }
+    pub const fn wrapping_from_index(i: usize) -> Square { SQUARES[i & 63] }
 
+    /// Returns the square for the given index modulo 64 like wrapping_from_index().
+    #[deprecated(note = "wraps around silently, use wrapping_from_index() to keep this \
+                         or try_from() to reject indexes out of range")]
     #[inline]
-    pub fn from_file_rank(f: File, r: Rank) -> Square {
+    pub const fn from_index(i: usize) -> Square { Square::wrapping_from_index(i) }
+
+    /// Returns the square for the given index (0=a1 to 63=h8) or None if the
+    /// index is out of range.
+    #[inline]
+    pub const fn try_from_index(i: usize) -> Option<Square> {
+        if i < SQUARES_LEN { Some(SQUARES[i]) } else { None }
+    }
+
+    #[inline]
+    pub const fn from_file_rank(f: File, r: Rank) -> Square {
         SQUARES[(r.to_index() << 3) + f.to_index()]
     }
 
+    /// Returns an iterator over all squares from a1 to h8.
+    #[inline]
+    pub fn iter() -> impl DoubleEndedIterator<Item = Square> + ExactSizeIterator {
+        SQUARES.iter().copied()
+    }
+
+    /// Returns an iterator over the squares from `from` to `to` (both inclusive)
+    /// in index order. The iterator is empty if `from` is greater than `to`.
+    #[inline]
+    pub fn range(from: Square, to: Square) -> impl DoubleEndedIterator<Item = Square> + ExactSizeIterator {
+        let end = if from <= to { to.to_index() + 1 } else { from.to_index() };
+        SQUARES[from.to_index()..end].iter().copied()
+    }
+
     /// Returns the corresponding File for the Square
     #[inline]
-    pub fn file_of(&self) -> File { File::from_index(self.to_index()) }
+    pub const fn file_of(&self) -> File { File::wrapping_from_index(self.to_index()) }
 
     /// Returns the corresponding Rank for the Square
     #[inline]
-    pub fn rank_of(&self) -> Rank { Rank::from_index(self.to_index()) }
+    pub const fn rank_of(&self) -> Rank { Rank::from_square_index(self.to_index()) }
+
+    /// Returns the Chebyshev distance (number of king moves) to the other square.
+    #[inline]
+    pub fn distance(&self, other: Square) -> u32 { DISTANCE[self.to_index()][other.to_index()] as u32 }
+
+    /// Returns the Manhattan distance (file distance plus rank distance) to the other square.
+    #[inline]
+    pub fn manhattan_distance(&self, other: Square) -> u32 {
+        MANHATTAN_DISTANCE[self.to_index()][other.to_index()] as u32
+    }
+
+    /// Returns the Chebyshev distance to the nearest of d4, e4, d5 and e5 (0-3).
+    #[inline]
+    pub fn center_distance(&self) -> u32 { CENTER_DISTANCE[self.to_index()] as u32 }
+
+    /// Returns the Chebyshev distance to the nearest edge of the board (0-3).
+    #[inline]
+    pub fn edge_distance(&self) -> u32 { EDGE_DISTANCE[self.to_index()] as u32 }
+
+    /// Returns true if this is a light square (h1 is light, a1 is dark).
+    #[inline]
+    pub fn is_light(&self) -> bool { (self.file_of().to_index() + self.rank_of().to_index()) & 1 == 1 }
+
+    /// Returns true if this is a dark square (a1 is dark, h1 is light).
+    #[inline]
+    pub fn is_dark(&self) -> bool { !self.is_light() }
+
+    /// Returns the square mirrored at the horizontal middle line (a1 <-> a8).
+    #[inline]
+    pub fn flip_vertical(&self) -> Square { Square::wrapping_from_index(self.to_index() ^ 56) }
+
+    /// Returns the square mirrored at the vertical middle line (a1 <-> h1).
+    #[inline]
+    pub fn mirror_horizontal(&self) -> Square { Square::wrapping_from_index(self.to_index() ^ 7) }
+
+    /// Returns the square mirrored at the a1-h8 diagonal (a8 <-> h1).
+    #[inline]
+    pub fn flip_diagonal(&self) -> Square {
+        Square::from_file_rank(File::wrapping_from_index(self.rank_of().to_index()), Rank::wrapping_from_index(self.file_of().to_index()))
+    }
+
+    /// Returns the square as seen from the given color's side of the board.
+    /// For White this is the square itself, for Black it is flipped vertically,
+    /// so tables written from White's point of view can be used for Black.
+    #[inline]
+    pub fn relative_to(&self, c: Color) -> Square {
+        match c {
+            Color::White => *self,
+            Color::Black => self.flip_vertical()
+        }
+    }
 
 }
 
+impl Square {
+
+    /// Parses a square in algebraic notation ("e4") from ASCII bytes without
+    /// allocating. The input must consist of exactly the two characters of the
+    /// square; trailing input is rejected. The file may be upper or lower case.
+    /// All bytes before the failing one are ASCII, so the error offset is also a
+    /// character offset.
+    pub fn parse_bytes(bytes: &[u8]) -> Result<Square, Error> {
+        let invalid = |offset| Error::InvalidSquare { input: String::from_utf8_lossy(bytes).into_owned(), offset };
+        let file = bytes.first().and_then(|c| File::from_ascii(*c)).ok_or_else(|| invalid(0))?;
+        let rank = bytes.get(1).and_then(|c| Rank::from_ascii(*c)).ok_or_else(|| invalid(1))?;
+        if bytes.len() > 2 {
+            return Err(invalid(2));
+        }
+        Ok(Square::from_file_rank(file, rank))
+    }
+
+}
+
+/// Parses a square in algebraic notation ("e4"). See Square::parse_bytes.
+/// Unlike Color, File and Rank there is no TryFrom<char> as a square takes
+/// two characters, file and rank.
 impl FromStr for Square {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() < 2 {
-            return Err(Error::InvalidSquare);
-        }
-
-        let file = File::from_str(&s[0..1]);
-        match file {
-            Err(Error::InvalidFile) => return Err(Error::InvalidSquare),
-            _ => ()
-        }
-        let rank = Rank::from_str(&s[1..2]);
-        match rank {
-            Err(Error::InvalidRank) => return Err(Error::InvalidSquare),
-            _ => ()
-        }
+        Square::parse_bytes(s.as_bytes())
+    }
+}
 
-        Ok(Square::from_file_rank(file.unwrap(),rank.unwrap()))
+impl TryFrom<usize> for Square {
+    type Error = Error;
+    fn try_from(i: usize) -> Result<Self, Self::Error> {
+        Square::try_from_index(i).ok_or_else(|| Error::InvalidSquare { input: i.to_string(), offset: 0 })
     }
 }
 
+impl TryFrom<u8> for Square {
+    type Error = Error;
+    fn try_from(i: u8) -> Result<Self, Self::Error> { Square::try_from(i as usize) }
+}
+
 impl fmt::Display for Square {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}{}",
-            format!("{}", self.file_of()),
-            format!("{}", self.rank_of())
-        )
+        write!(f, "{}{}", self.file_of(), self.rank_of())
     }
 }
 
@@ -232,14 +388,29 @@ mod tests {
     }
 
     #[test]
-    fn test_from_index() {
-        assert_eq!(Square::A1, Square::from_index(0));
-        assert_eq!(Square::H1, Square::from_index(7));
-        assert_eq!(Square::A3, Square::from_index(16));
-        assert_eq!(Square::H3, Square::from_index(23));
-        assert_eq!(Square::A8, Square::from_index(56));
-        assert_eq!(Square::H8, Square::from_index(63));
-        assert_eq!(Square::A1, Square::from_index(64));
+    fn test_wrapping_from_index() {
+        assert_eq!(Square::A1, Square::wrapping_from_index(0));
+        assert_eq!(Square::H1, Square::wrapping_from_index(7));
+        assert_eq!(Square::A3, Square::wrapping_from_index(16));
+        assert_eq!(Square::H3, Square::wrapping_from_index(23));
+        assert_eq!(Square::A8, Square::wrapping_from_index(56));
+        assert_eq!(Square::H8, Square::wrapping_from_index(63));
+        assert_eq!(Square::A1, Square::wrapping_from_index(64));
+        #[allow(deprecated)]
+        let old = Square::from_index(70);
+        assert_eq!(Square::G1, old);
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(Some(Square::H8), Square::try_from_index(63));
+        assert_eq!(None, Square::try_from_index(64));
+        assert_eq!(Ok(Square::E4), Square::try_from(28usize));
+        assert_eq!(Ok(Square::A3), Square::try_from(16u8));
+        assert_eq!(Err(Error::InvalidSquare { input: "70".to_string(), offset: 0 }), Square::try_from(70u8));
+        // usable in constant expressions
+        const E4: Option<Square> = Square::try_from_index(28);
+        assert_eq!(Some(Square::E4), E4);
     }
 
     #[test]
@@ -252,6 +423,20 @@ mod tests {
         assert_eq!(Square::H8, Square::from_file_rank(File::H,Rank::Eight));
     }
 
+    #[test]
+    fn test_iter() {
+        assert_eq!(64, Square::iter().len());
+        assert_eq!(Some(Square::H8), Square::iter().next_back());
+        assert!(Square::iter().eq(SQUARES.iter().copied()));
+        let squares: Vec<Square> = Square::range(Square::F1, Square::B2).collect();
+        assert_eq!(vec![Square::F1, Square::G1, Square::H1, Square::A2, Square::B2], squares);
+        assert_eq!(1, Square::range(Square::E4, Square::E4).len());
+        assert_eq!(0, Square::range(Square::E5, Square::E4).len());
+        let squares: Vec<Square> = Square::range(Square::A8, Square::H8).rev().collect();
+        assert_eq!(Square::H8, squares[0]);
+        assert_eq!(Square::A8, squares[7]);
+    }
+
     #[test]
     fn test_file_of() {
         assert_eq!(File::A, Square::A1.file_of());
@@ -296,7 +481,41 @@ mod tests {
         let result = Square::from_str("h9");
         match result {
             Ok(_) => panic!("Test failed"),
-            Err(e) => assert_eq!(Error::InvalidSquare, e),
+            Err(e) => assert_eq!(Error::InvalidSquare { input: "h9".to_string(), offset: 1 }, e),
+        }
+
+        let result = Square::from_str("z9");
+        match result {
+            Ok(_) => panic!("Test failed"),
+            Err(e) => assert_eq!(Error::InvalidSquare { input: "z9".to_string(), offset: 0 }, e),
+        }
+
+        let result = Square::from_str("e4xyz");
+        match result {
+            Ok(_) => panic!("Test failed"),
+            Err(e) => assert_eq!(Error::InvalidSquare { input: "e4xyz".to_string(), offset: 2 }, e),
+        }
+
+        let result = Square::from_str("äb");
+        match result {
+            Ok(_) => panic!("Test failed"),
+            Err(e) => assert_eq!(Error::InvalidSquare { input: "äb".to_string(), offset: 0 }, e),
+        }
+
+        assert_eq!(Err(Error::InvalidSquare { input: "e4ä".to_string(), offset: 2 }), Square::from_str("e4ä"));
+        assert_eq!(Err(Error::InvalidSquare { input: "e".to_string(), offset: 1 }), Square::from_str("e"));
+        assert_eq!(Err(Error::InvalidSquare { input: "".to_string(), offset: 0 }), Square::from_str(""));
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        assert_eq!(Ok(Square::E4), Square::parse_bytes(b"e4"));
+        assert_eq!(Ok(Square::H8), Square::parse_bytes(b"H8"));
+        assert_eq!(Err(Error::InvalidSquare { input: "e4 ".to_string(), offset: 2 }), Square::parse_bytes(b"e4 "));
+        assert_eq!(Err(Error::InvalidSquare { input: "e0".to_string(), offset: 1 }), Square::parse_bytes(b"e0"));
+        assert!(Square::parse_bytes(&[0xC3, 0xA4]).is_err());
+        for sq in SQUARES {
+            assert_eq!(Ok(sq), Square::parse_bytes(sq.to_string().as_bytes()));
         }
     }
 
@@ -307,4 +526,71 @@ mod tests {
         assert_eq!("h8", format!("{}", Square::H8));
     }
 
+    #[test]
+    fn test_distance() {
+        assert_eq!(0, Square::E4.distance(Square::E4));
+        assert_eq!(7, Square::A1.distance(Square::H8));
+        assert_eq!(7, Square::A1.distance(Square::A8));
+        assert_eq!(2, Square::G1.distance(Square::E2));
+        assert_eq!(14, Square::A1.manhattan_distance(Square::H8));
+        assert_eq!(3, Square::G1.manhattan_distance(Square::E2));
+        for a in SQUARES {
+            for b in SQUARES {
+                let file_dist = a.file_of().to_index().abs_diff(b.file_of().to_index()) as u32;
+                let rank_dist = a.rank_of().to_index().abs_diff(b.rank_of().to_index()) as u32;
+                assert_eq!(file_dist.max(rank_dist), a.distance(b));
+                assert_eq!(file_dist + rank_dist, a.manhattan_distance(b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_center_edge_distance() {
+        assert_eq!(0, Square::E4.center_distance());
+        assert_eq!(0, Square::D5.center_distance());
+        assert_eq!(1, Square::C6.center_distance());
+        assert_eq!(3, Square::A1.center_distance());
+        assert_eq!(3, Square::H5.center_distance());
+        assert_eq!(0, Square::A4.edge_distance());
+        assert_eq!(0, Square::E8.edge_distance());
+        assert_eq!(1, Square::B7.edge_distance());
+        assert_eq!(3, Square::E5.edge_distance());
+        for sq in SQUARES {
+            assert_eq!(3, sq.center_distance() + sq.edge_distance());
+        }
+    }
+
+    #[test]
+    fn test_square_color() {
+        assert!(Square::A1.is_dark());
+        assert!(Square::H1.is_light());
+        assert!(Square::D1.is_light());
+        assert!(Square::E1.is_dark());
+        assert!(Square::H8.is_dark());
+        assert_eq!(32, SQUARES.iter().filter(|sq| sq.is_light()).count());
+    }
+
+    #[test]
+    fn test_mirroring() {
+        assert_eq!(Square::A8, Square::A1.flip_vertical());
+        assert_eq!(Square::E2, Square::E7.flip_vertical());
+        assert_eq!(Square::H1, Square::A1.mirror_horizontal());
+        assert_eq!(Square::D4, Square::E4.mirror_horizontal());
+        assert_eq!(Square::A8, Square::H1.flip_diagonal());
+        assert_eq!(Square::C2, Square::B3.flip_diagonal());
+        assert_eq!(Square::E5, Square::E5.flip_diagonal());
+        for sq in SQUARES {
+            assert_eq!(sq, sq.flip_vertical().flip_vertical());
+            assert_eq!(sq, sq.mirror_horizontal().mirror_horizontal());
+            assert_eq!(sq, sq.flip_diagonal().flip_diagonal());
+        }
+    }
+
+    #[test]
+    fn test_relative_to() {
+        assert_eq!(Square::E2, Square::E2.relative_to(Color::White));
+        assert_eq!(Square::E7, Square::E2.relative_to(Color::Black));
+        assert_eq!(Square::H8, Square::H1.relative_to(Color::Black));
+    }
+
 }
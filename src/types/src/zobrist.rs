@@ -0,0 +1,175 @@
+/*
+ * FrankyRust
+ * Copyright (c) 2022 Frank Kopp
+ *
+ * MIT License
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Random keys for Zobrist hashing of positions.
+//! The keys are generated at compile time by a seeded PRNG so they are identical
+//! across runs and builds.
+
+use crate::CastlingRights;
+use crate::Color;
+use crate::File;
+use crate::Piece;
+use crate::Square;
+use crate::CASTLING_RIGHTS_LEN;
+use crate::COLORS_LEN;
+use crate::FILES_LEN;
+use crate::PIECES_LEN;
+use crate::SQUARES_LEN;
+
+/// A Zobrist hash key
+pub type Key = u64;
+
+const SEED: u64 = 1_070_372;
+
+/// SplitMix64 step: returns the next state and the random value for it.
+const fn next(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+struct Keys {
+    pieces: [[Key; SQUARES_LEN]; PIECES_LEN],
+    side: [Key; COLORS_LEN],
+    castling: [Key; CASTLING_RIGHTS_LEN],
+    en_passant: [Key; FILES_LEN],
+}
+
+const fn init_keys() -> Keys {
+    let mut keys = Keys {
+        pieces: [[0; SQUARES_LEN]; PIECES_LEN],
+        side: [0; COLORS_LEN],
+        castling: [0; CASTLING_RIGHTS_LEN],
+        en_passant: [0; FILES_LEN],
+    };
+    let mut state = SEED;
+    let mut p = 0;
+    while p < PIECES_LEN {
+        let mut sq = 0;
+        while sq < SQUARES_LEN {
+            let (s, key) = next(state);
+            state = s;
+            keys.pieces[p][sq] = key;
+            sq += 1;
+        }
+        p += 1;
+    }
+    let mut c = 0;
+    while c < COLORS_LEN {
+        let (s, key) = next(state);
+        state = s;
+        keys.side[c] = key;
+        c += 1;
+    }
+    // castling keys are built from one key per single right so the key of
+    // a combination is the xor of its rights and the empty set is zero
+    let mut rights = [0; 4];
+    let mut r = 0;
+    while r < 4 {
+        let (s, key) = next(state);
+        state = s;
+        rights[r] = key;
+        r += 1;
+    }
+    let mut i = 0;
+    while i < CASTLING_RIGHTS_LEN {
+        let mut r = 0;
+        while r < 4 {
+            if i & (1 << r) != 0 {
+                keys.castling[i] ^= rights[r];
+            }
+            r += 1;
+        }
+        i += 1;
+    }
+    let mut f = 0;
+    while f < FILES_LEN {
+        let (s, key) = next(state);
+        state = s;
+        keys.en_passant[f] = key;
+        f += 1;
+    }
+    keys
+}
+
+static KEYS: Keys = init_keys();
+
+/// Returns the key for the given piece standing on the given square.
+#[inline]
+pub fn piece(p: Piece, sq: Square) -> Key { KEYS.pieces[p.index()][sq.to_index()] }
+
+/// Returns the key for the given side to move.
+#[inline]
+pub fn side(c: Color) -> Key { KEYS.side[c.index()] }
+
+/// Returns the key for the given castling rights. The key for no rights is zero.
+#[inline]
+pub fn castling(cr: CastlingRights) -> Key { KEYS.castling[cr.index()] }
+
+/// Returns the key for an en passant square on the given file.
+#[inline]
+pub fn en_passant(f: File) -> Key { KEYS.en_passant[f.to_index()] }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{COLORS, FILES, PIECES, SQUARES};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_unique() {
+        let mut keys = HashSet::new();
+        for p in PIECES {
+            for sq in SQUARES {
+                assert!(keys.insert(piece(p, sq)));
+            }
+        }
+        for c in COLORS {
+            assert!(keys.insert(side(c)));
+        }
+        for i in 1..CASTLING_RIGHTS_LEN {
+            assert!(keys.insert(castling(CastlingRights::try_from(i as u8).unwrap())));
+        }
+        for f in FILES {
+            assert!(keys.insert(en_passant(f)));
+        }
+        assert!(!keys.contains(&0));
+    }
+
+    #[test]
+    fn test_castling() {
+        assert_eq!(0, castling(CastlingRights::NONE));
+        assert_eq!(castling(CastlingRights::ANY),
+                   castling(CastlingRights::WHITE) ^ castling(CastlingRights::BLACK));
+        assert_eq!(castling(CastlingRights::WHITE),
+                   castling(CastlingRights::WHITE_OO) ^ castling(CastlingRights::WHITE_OOO));
+    }
+
+    #[test]
+    fn test_deterministic() {
+        // the keys must never change between builds
+        assert_eq!(0xf076_2eaa_d5c5_6fe8, piece(Piece::WhitePawn, Square::A1));
+        assert_eq!(0xf17b_c432_0a13_7bd2, side(Color::Black));
+    }
+
+}